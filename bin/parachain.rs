@@ -1,13 +1,8 @@
-use serde::{Deserialize, Serialize};
-use std::{
-	collections::HashMap,
-	fs::File,
-	io::{self, Write},
-	time::SystemTime,
+use creek::{
+	primitives::BlockAt, utils::vc_registry_snapshot::VCRegistrySnapshot, Creek, CreekExplorer,
 };
-
-use creek::{Creek, CreekExplorer, ParachainOp};
 use sp_core::{sr25519, Pair};
+use substrate_api_client::GetChainInfo;
 
 pub const LOCAL: (&str, &str) = ("wss://localhost:9944", "wss://localhost:2600");
 pub const INTERNAL: (&str, &str) =
@@ -45,37 +40,11 @@ fn main() {
 	// 2: Set this alice as signer.
 	let creek = Creek::explorer(env.get_url().0, env.get_url().1, alice.into()).unwrap();
 
-	// 3. Query VCRegistry Data
-	let mut contexts = creek.vc_registry().unwrap();
+	// 3. Snapshot VCRegistry at the latest finalized block
+	let finalized = creek.parachain_client.api.get_finalized_head().unwrap().unwrap();
+	let snapshot = VCRegistrySnapshot::take(&creek, BlockAt::Hash(finalized)).unwrap();
 
 	// 4. Backup VCRegistry
-	let _ = contexts.write();
-}
-
-#[derive(Serialize, Deserialize)]
-struct Data {
-	map: HashMap<String, String>,
-}
-
-pub trait WriteToFile {
-	fn write(&mut self) -> io::Result<()>;
-}
-
-impl WriteToFile for HashMap<String, String> {
-	fn write(&mut self) -> io::Result<()> {
-		let timestamp = SystemTime::now()
-			.duration_since(SystemTime::UNIX_EPOCH)
-			.expect("Failed to retrieve timestamp")
-			.as_secs();
-
-		let data = Data { map: self.clone() };
-		let json = serde_json::to_string(&data)?;
-
-		let backup_path = "./bin/VCRegistryBackup";
-		let file_name = format!("{}/{}-vcregistry.json", backup_path, timestamp);
-		let mut file = File::create(file_name)?;
-		file.write_all(json.as_bytes())?;
-
-		Ok(())
-	}
+	let path = snapshot.save("./bin/VCRegistryBackup").unwrap();
+	println!("VCRegistry snapshot at {} saved to {:?}", snapshot.block_hash, path);
 }
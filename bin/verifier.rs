@@ -0,0 +1,609 @@
+//! Local HTTP API verifying Litentry credentials and presentations.
+//!
+//! ```text
+//! verifier [--listen 127.0.0.1:8787] --enclaves <snapshot.json> [--registry <snapshot.json>]
+//! verifier [--listen 127.0.0.1:8787] --parachain <wss://...>
+//! ```
+//!
+//! * `POST /verify/credential`: the credential JSON, in either data model layout.
+//! * `POST /verify/presentation`: `{ "presentation": {...}, "challenge": "...", "domain": "..." }`
+//! * `GET /health`
+//!
+//! Both return the report of each credential, see `utils::vc::VerificationReport`. The connections
+//! are served by a pool of `WORKERS` threads.
+
+use creek::{
+	primitives::{presentation::Presentation, vc::Credential},
+	utils::{
+		enclave_registry::EnclaveRegistrySnapshot,
+		presentation::{presented_credentials, verify_presentation_holder},
+		raw_json::member,
+		vc::{
+			verify_vc_raw_with, verify_versioned_vc_with, CheckOutcome, TimePolicy,
+			VerificationReport, VerifyContext, VersionedCredential,
+		},
+		vc_registry_snapshot::VCRegistrySnapshot,
+	},
+	Creek, CreekExplorer,
+};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sp_core::{ed25519, sr25519, Pair};
+use std::{
+	env,
+	io::{self, BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream},
+	panic::{self, AssertUnwindSafe},
+	process, thread,
+	time::{Duration, Instant},
+};
+
+const USAGE: &str =
+	"Usage: verifier [--listen <addr>] --enclaves <snapshot> [--registry <snapshot>]
+       verifier [--listen <addr>] --parachain <endpoint>";
+const DEFAULT_LISTEN: &str = "127.0.0.1:8787";
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// Total time a client has to send its request, however slowly it sends it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections served at the same time, a slow client only holds up one of them.
+const WORKERS: usize = 8;
+
+/// Where the issuer enclaves and the credential status are looked up, shared by the workers.
+enum Config {
+	/// Fully offline
+	Snapshots { enclaves: EnclaveRegistrySnapshot, registry: Option<VCRegistrySnapshot> },
+	/// Latest state of the parachain at this endpoint
+	Parachain(String),
+}
+
+impl Config {
+	/// The `Source` of one worker. Each worker connects to the parachain on its own, so that the
+	/// clients of `Creek` are never shared between threads.
+	fn source(&self) -> Result<Source<'_>, String> {
+		match self {
+			Config::Snapshots { enclaves, registry } =>
+				Ok(Source::Snapshots { enclaves, registry: registry.as_ref() }),
+			Config::Parachain(endpoint) => {
+				// Only storage is read, the signer and the worker are never used.
+				let signer = sr25519::Pair::generate().0;
+				let creek = Creek::explorer(endpoint, "", signer.into())
+					.map_err(|e| format!("Failed to connect to {}: {:?}", endpoint, e))?;
+				Ok(Source::Parachain(Box::new(creek)))
+			},
+		}
+	}
+}
+
+/// The lookups of one worker, see `Config::source`.
+enum Source<'a> {
+	Snapshots { enclaves: &'a EnclaveRegistrySnapshot, registry: Option<&'a VCRegistrySnapshot> },
+	Parachain(Box<Creek>),
+}
+
+impl Source<'_> {
+	fn context(&self, vc_pubkey: ed25519::Public) -> VerifyContext<'_> {
+		let ctx = VerifyContext::new(vc_pubkey).with_time(TimePolicy::now());
+		match self {
+			Source::Snapshots { enclaves, registry } => {
+				let ctx = ctx.with_enclaves(*enclaves);
+				match registry {
+					Some(registry) => ctx.with_registry(*registry),
+					None => ctx,
+				}
+			},
+			Source::Parachain(creek) =>
+				ctx.with_enclaves(creek.as_ref()).with_registry(creek.as_ref()),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct PresentationRequest {
+	presentation: Presentation,
+	challenge: String,
+	domain: String,
+}
+
+struct Request {
+	method: String,
+	path: String,
+	body: Vec<u8>,
+}
+
+fn main() {
+	env_logger::init();
+
+	let (listen, config) = match parse_args(env::args().skip(1).collect()) {
+		Ok(config) => config,
+		Err(e) => {
+			eprintln!("{}\n{}", e, USAGE);
+			process::exit(1);
+		},
+	};
+
+	let listener = TcpListener::bind(&listen).unwrap_or_else(|e| {
+		eprintln!("Failed to listen on {}: {:?}", listen, e);
+		process::exit(1);
+	});
+	info!("Verifier listening on http://{}", listen);
+
+	if let Err(e) = serve(&listener, &config, WORKERS) {
+		eprintln!("{}", e);
+		process::exit(1);
+	}
+}
+
+/// Serves the connections of `listener` on `workers` threads, each with its own `Source`. Only
+/// returns when the first `Source` can't be built.
+fn serve(listener: &TcpListener, config: &Config, workers: usize) -> Result<(), String> {
+	// Built before the other workers start, so that a wrong config fails at once.
+	let source = config.source()?;
+
+	thread::scope(|scope| {
+		for _ in 1..workers {
+			scope.spawn(|| match config.source() {
+				Ok(source) => accept(listener, &source),
+				Err(e) => warn!("Failed to start a worker: {}", e),
+			});
+		}
+		accept(listener, &source);
+	});
+	Ok(())
+}
+
+fn accept(listener: &TcpListener, source: &Source) {
+	for stream in listener.incoming() {
+		match stream {
+			Ok(stream) =>
+				if let Err(e) = handle(stream, source) {
+					warn!("Failed to handle request: {:?}", e);
+				},
+			Err(e) => warn!("Failed to accept connection: {:?}", e),
+		}
+	}
+}
+
+fn parse_args(args: Vec<String>) -> Result<(String, Config), String> {
+	let mut listen = DEFAULT_LISTEN.to_string();
+	let (mut enclaves, mut registry, mut parachain) = (None, None, None);
+
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		let value = args.next().ok_or_else(|| format!("Missing value of {}", arg))?;
+		match arg.as_str() {
+			"--listen" => listen = value,
+			"--enclaves" => enclaves = Some(value),
+			"--registry" => registry = Some(value),
+			"--parachain" => parachain = Some(value),
+			_ => return Err(format!("Unknown argument {}", arg)),
+		}
+	}
+
+	let config = match (enclaves, registry, parachain) {
+		(Some(enclaves), registry, None) => Config::Snapshots {
+			enclaves: EnclaveRegistrySnapshot::load(&enclaves)
+				.map_err(|e| format!("Failed to load {}: {:?}", enclaves, e))?,
+			registry: registry
+				.map(|registry| {
+					VCRegistrySnapshot::load(&registry)
+						.map_err(|e| format!("Failed to load {}: {:?}", registry, e))
+				})
+				.transpose()?,
+		},
+		(None, None, Some(endpoint)) => Config::Parachain(endpoint),
+		_ => return Err("Either --enclaves or --parachain must be provided".to_string()),
+	};
+
+	Ok((listen, config))
+}
+
+fn handle(mut stream: TcpStream, source: &Source) -> io::Result<()> {
+	stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+	let reader = DeadlineReader { stream: &stream, deadline: Instant::now() + REQUEST_TIMEOUT };
+
+	// A panic while handling a request must not take the server down.
+	let (status, body) = panic::catch_unwind(AssertUnwindSafe(|| respond(reader, source)))
+		.unwrap_or_else(|_| (500, json!({ "error": "Internal error" })));
+
+	let body = body.to_string();
+	write!(stream, "HTTP/1.1 {} {}\r\n", status, reason_phrase(status))?;
+	write!(stream, "Content-Type: application/json\r\nContent-Length: {}\r\n", body.len())?;
+	write!(stream, "Connection: close\r\n\r\n{}", body)?;
+	stream.flush()
+}
+
+/// Reads from `stream` until `deadline`, each read waits at most for the time that's left.
+struct DeadlineReader<'a> {
+	stream: &'a TcpStream,
+	deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let remaining = self
+			.deadline
+			.checked_duration_since(Instant::now())
+			.filter(|remaining| !remaining.is_zero())
+			.ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Request timed out"))?;
+		self.stream.set_read_timeout(Some(remaining))?;
+		self.stream.read(buf)
+	}
+}
+
+/// The status and body of the response to the request read from `reader`.
+fn respond(reader: impl Read, source: &Source) -> (u16, Value) {
+	match read_request(reader) {
+		Ok(request) => {
+			info!("{} {}", request.method, request.path);
+			route(&request, source)
+		},
+		Err(e) => (400, json!({ "error": e })),
+	}
+}
+
+fn read_request(reader: impl Read) -> Result<Request, String> {
+	let mut reader = BufReader::new(reader);
+
+	let mut line = String::new();
+	reader.read_line(&mut line).map_err(|e| format!("{:?}", e))?;
+	let mut parts = line.split_whitespace();
+	let (method, path) = match (parts.next(), parts.next()) {
+		(Some(method), Some(path)) => (method.to_string(), path.to_string()),
+		_ => return Err("Malformed request line".to_string()),
+	};
+
+	let mut content_length = 0;
+	loop {
+		line.clear();
+		reader.read_line(&mut line).map_err(|e| format!("{:?}", e))?;
+		let header = line.trim_end();
+		if header.is_empty() {
+			break
+		}
+		if let Some((name, value)) = header.split_once(':') {
+			if name.eq_ignore_ascii_case("content-length") {
+				content_length =
+					value.trim().parse().map_err(|_| "Invalid Content-Length".to_string())?;
+			}
+		}
+	}
+	if content_length > MAX_BODY_SIZE {
+		return Err(format!("Body larger than {} bytes", MAX_BODY_SIZE))
+	}
+
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body).map_err(|e| format!("{:?}", e))?;
+
+	Ok(Request { method, path, body })
+}
+
+fn route(request: &Request, source: &Source) -> (u16, Value) {
+	let result = match (request.method.as_str(), request.path.as_str()) {
+		("GET", "/health") => Ok(json!({ "status": "ok" })),
+		("POST", "/verify/credential") => verify_credential(&request.body, source),
+		("POST", "/verify/presentation") => verify_vp(&request.body, source),
+		(_, "/health" | "/verify/credential" | "/verify/presentation") =>
+			return (405, json!({ "error": "Method not allowed" })),
+		_ => return (404, json!({ "error": "Not found" })),
+	};
+
+	match result {
+		Ok(body) => (200, body),
+		Err(e) => (400, json!({ "error": e })),
+	}
+}
+
+fn verify_credential(body: &[u8], source: &Source) -> Result<Value, String> {
+	let json = std::str::from_utf8(body).map_err(|e| format!("{:?}", e))?;
+	let vc = VersionedCredential::from_json(json)?;
+	let ctx = source.context(vc_pubkey(&vc.to_v1()?)?);
+	// The proof of a v1 credential is checked over the exact bytes the worker returned.
+	let report = match vc {
+		VersionedCredential::V1(_) => verify_vc_raw_with(&ctx, body)?,
+		VersionedCredential::V2(_) => verify_versioned_vc_with(&ctx, &vc)?,
+	};
+
+	Ok(report_json(&report))
+}
+
+/// The presentation is verified when its holder checks pass and each credential is verified, with
+/// the `vc_pubkey` of the enclave which issued it. The credentials are checked over their JSON as
+/// presented.
+fn verify_vp(body: &[u8], source: &Source) -> Result<Value, String> {
+	let request: PresentationRequest =
+		serde_json::from_slice(body).map_err(|e| format!("{:?}", e))?;
+	let vp = &request.presentation;
+	let json = member(body, "presentation")?.ok_or("Presentation is missing")?;
+
+	let errors = match verify_presentation_holder(vp, &request.challenge, &request.domain) {
+		Ok(()) => vec![],
+		Err(errors) => errors.iter().map(|e| format!("{:?}", e)).collect::<Vec<_>>(),
+	};
+
+	let reports: Vec<_> = vp
+		.verifiable_credential
+		.iter()
+		.zip(presented_credentials(json)?)
+		.map(|(vc, json)| {
+			vc_pubkey(vc)
+				.and_then(|vc_pubkey| verify_vc_raw_with(&source.context(vc_pubkey), json))
+				.unwrap_or_else(|e| VerificationReport::failed(&e))
+		})
+		.collect();
+	let verified = errors.is_empty() && reports.iter().all(VerificationReport::is_verified);
+	let credentials: Vec<_> = reports.iter().map(report_json).collect();
+
+	Ok(json!({ "verified": verified, "errors": errors, "credentials": credentials }))
+}
+
+/// The key the credential claims to be signed with, the issuer check makes sure it belongs to a
+/// registered enclave.
+fn vc_pubkey(vc: &Credential) -> Result<ed25519::Public, String> {
+	let verification_method = &vc.proof.as_ref().ok_or("Proof is missing")?.verification_method;
+	let bytes = hex::decode(verification_method.trim_start_matches("0x"))
+		.map_err(|e| format!("Invalid verificationMethod: {:?}", e))?;
+	let raw: [u8; 32] = bytes.try_into().map_err(|_| "Invalid verificationMethod length")?;
+
+	Ok(ed25519::Public::from_raw(raw))
+}
+
+fn report_json(report: &VerificationReport) -> Value {
+	let checks: Vec<Value> = report
+		.checks
+		.iter()
+		.map(|(check, outcome)| {
+			let (outcome, reason) = match outcome {
+				CheckOutcome::Pass => ("pass", None),
+				CheckOutcome::Fail(reason) => ("fail", Some(reason)),
+				CheckOutcome::Skipped(reason) => ("skipped", Some(reason)),
+			};
+			json!({ "check": check.name(), "outcome": outcome, "reason": reason })
+		})
+		.collect();
+
+	json!({ "verified": report.is_verified(), "checks": checks })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		400 => "Bad Request",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		_ => "Internal Server Error",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use creek::{
+		primitives::{assertion::Assertion, enclave::Enclave, identity::Identity, AccountId},
+		utils::vc_builder::CredentialBuilder,
+	};
+
+	const MRENCLAVE: [u8; 32] = [0x7a; 32];
+
+	fn pair() -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[0x42; 32])
+	}
+
+	/// The `vc_pubkey` of another registered enclave.
+	fn other_pair() -> ed25519::Pair {
+		ed25519::Pair::from_seed(&[0x24; 32])
+	}
+
+	fn alice() -> sr25519::Pair {
+		sr25519::Pair::from_string("//Alice", None).unwrap()
+	}
+
+	/// The enclave where `pair` is the account and `vc_pubkey`.
+	fn enclave(pair: &ed25519::Pair) -> Enclave<AccountId, String> {
+		let public = pair.public();
+		Enclave {
+			pubkey: AccountId::new(public.0),
+			mr_enclave: MRENCLAVE,
+			timestamp: 0,
+			url: "wss://localhost:2000".to_string(),
+			shielding_key: None,
+			vc_pubkey: Some(public.0.to_vec()),
+			sgx_mode: Default::default(),
+			sgx_metadata: Default::default(),
+		}
+	}
+
+	/// A config where the enclaves of `pair()` and `other_pair()` are registered.
+	fn config() -> Config {
+		Config::Snapshots {
+			enclaves: EnclaveRegistrySnapshot::new(
+				vec![],
+				&[enclave(&pair()), enclave(&other_pair())],
+			),
+			registry: None,
+		}
+	}
+
+	fn credential() -> Credential {
+		issued_by(&pair())
+	}
+
+	fn issued_by(pair: &ed25519::Pair) -> Credential {
+		CredentialBuilder::new(&Assertion::A1, &Identity::from(alice().public()))
+			.with_issuer(&pair.public().0, &MRENCLAVE)
+			.issued_at(1696663438000)
+			.sign(pair)
+			.unwrap()
+	}
+
+	fn post(path: &str, body: &str) -> String {
+		format!("POST {} HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body)
+	}
+
+	/// The credentials are verified over the presentation JSON as it is, `json!` would sort its
+	/// members.
+	fn presentation_request(vp: &str, challenge: &str) -> String {
+		let body = format!(
+			r#"{{"presentation":{},"challenge":"{}","domain":"example.com"}}"#,
+			vp, challenge
+		);
+		post("/verify/presentation", &body)
+	}
+
+	/// The response of a worker of `config()` to `request`.
+	fn send(request: &str) -> (u16, Value) {
+		respond(request.as_bytes(), &config().source().unwrap())
+	}
+
+	#[test]
+	fn parse_args_works() {
+		let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+
+		assert!(parse_args(args(&[])).is_err());
+		assert!(parse_args(args(&["--listen"])).is_err());
+		assert!(parse_args(args(&["--unknown", "value"])).is_err());
+		assert!(parse_args(args(&["--enclaves", "/nonexistent.json"])).is_err());
+		assert!(parse_args(args(&["--enclaves", "a.json", "--parachain", "wss://a"])).is_err());
+
+		let path = env::temp_dir().join("verifier-parse-args-enclaves.json");
+		EnclaveRegistrySnapshot::new(vec![], &[]).save(&path).unwrap();
+		let (listen, config) =
+			parse_args(args(&["--listen", "0.0.0.0:80", "--enclaves", path.to_str().unwrap()]))
+				.unwrap();
+		assert_eq!(listen, "0.0.0.0:80");
+		assert!(matches!(config, Config::Snapshots { registry: None, .. }));
+
+		let (_, config) = parse_args(args(&["--parachain", "wss://a"])).unwrap();
+		assert!(matches!(config, Config::Parachain(endpoint) if endpoint == "wss://a"));
+	}
+
+	#[test]
+	fn read_request_works() {
+		let request = read_request(post("/verify/credential", "{}").as_bytes()).unwrap();
+		assert_eq!(request.method, "POST");
+		assert_eq!(request.path, "/verify/credential");
+		assert_eq!(request.body, b"{}");
+
+		let request = read_request("GET /health HTTP/1.1\r\n\r\n".as_bytes()).unwrap();
+		assert!(request.body.is_empty());
+
+		assert!(read_request("\r\n".as_bytes()).is_err());
+		// Shorter than its Content-Length
+		assert!(read_request("POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}".as_bytes()).is_err());
+	}
+
+	#[test]
+	fn valid_credential_is_verified() {
+		let body = serde_json::to_string(&credential()).unwrap();
+		let (status, report) = send(&post("/verify/credential", &body));
+		assert_eq!(status, 200);
+		assert_eq!(report["verified"], true, "{}", report);
+	}
+
+	#[test]
+	fn tampered_credential_is_not_verified() {
+		let mut vc = credential();
+		vc.credential_subject.values = vec![false];
+		let body = serde_json::to_string(&vc).unwrap();
+		let (status, report) = send(&post("/verify/credential", &body));
+		assert_eq!(status, 200);
+		assert_eq!(report["verified"], false);
+	}
+
+	#[test]
+	fn presented_credential_json_is_verified() {
+		// A credential with a member `Credential` doesn't know about, signed as the worker would.
+		let mut unsigned = serde_json::to_value(credential()).unwrap();
+		let mut proof = unsigned.as_object_mut().unwrap().remove("proof").unwrap();
+		unsigned["futureField"] = json!(["x"]);
+		let unsigned = unsigned.to_string();
+		proof["proofValue"] = json!(hex::encode(pair().sign(unsigned.as_bytes()).0));
+		let signed = format!("{},\"proof\":{}}}", &unsigned[..unsigned.len() - 1], proof);
+		let vc: Credential = serde_json::from_str(&signed).unwrap();
+
+		let vp = Presentation::sign(&alice().into(), vec![vc.clone()], "nonce", "example.com", 0)
+			.unwrap();
+		let vp = serde_json::to_string(&vp)
+			.unwrap()
+			.replace(&serde_json::to_string(&vc).unwrap(), &signed);
+		let (status, report) = send(&presentation_request(&vp, "nonce"));
+		assert_eq!(status, 200);
+		assert_eq!(report["verified"], true, "{}", report);
+	}
+
+	#[test]
+	fn presentation_of_two_enclaves_is_verified() {
+		let credentials = vec![credential(), issued_by(&other_pair())];
+		let vp =
+			Presentation::sign(&alice().into(), credentials, "nonce", "example.com", 0).unwrap();
+		let vp = serde_json::to_string(&vp).unwrap();
+		let (status, report) = send(&presentation_request(&vp, "nonce"));
+		assert_eq!(status, 200);
+		assert_eq!(report["verified"], true, "{}", report);
+		assert_eq!(report["credentials"][1]["verified"], true);
+
+		let (_, report) = send(&presentation_request(&vp, "other"));
+		assert_eq!(report["verified"], false);
+		assert_eq!(report["errors"].as_array().unwrap().len(), 1);
+		assert_eq!(report["credentials"][0]["verified"], true);
+
+		// A credential of an unregistered enclave
+		let credentials = vec![credential(), issued_by(&ed25519::Pair::from_seed(&[0x11; 32]))];
+		let vp =
+			Presentation::sign(&alice().into(), credentials, "nonce", "example.com", 0).unwrap();
+		let vp = serde_json::to_string(&vp).unwrap();
+		let (_, report) = send(&presentation_request(&vp, "nonce"));
+		assert_eq!(report["verified"], false);
+		assert_eq!(report["errors"], json!([]));
+		assert_eq!(report["credentials"][1]["verified"], false);
+	}
+
+	#[test]
+	fn slow_client_does_not_block_others() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		thread::spawn(move || serve(&listener, &config(), 2));
+
+		// Connected, but never sends its request
+		let _slow = TcpStream::connect(address).unwrap();
+		let mut stream = TcpStream::connect(address).unwrap();
+		stream.set_read_timeout(Some(REQUEST_TIMEOUT / 2)).unwrap();
+		stream.write_all(b"GET /health HTTP/1.1\r\n\r\n").unwrap();
+		let mut response = String::new();
+		stream.read_to_string(&mut response).unwrap();
+		assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+	}
+
+	#[test]
+	fn oversized_body_is_rejected() {
+		let request = format!(
+			"POST /verify/credential HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+			MAX_BODY_SIZE + 1
+		);
+		let (status, _) = send(&request);
+		assert_eq!(status, 400);
+	}
+
+	#[test]
+	fn invalid_content_length_is_rejected() {
+		let request = "POST /verify/credential HTTP/1.1\r\nContent-Length: -1\r\n\r\n{}";
+		let (status, body) = send(request);
+		assert_eq!(status, 400);
+		assert_eq!(body["error"], "Invalid Content-Length");
+	}
+
+	#[test]
+	fn unknown_routes_are_rejected() {
+		let (status, _) = send(&post("/verify", "{}"));
+		assert_eq!(status, 404);
+
+		let (status, _) = send("GET /verify/credential HTTP/1.1\r\n\r\n");
+		assert_eq!(status, 405);
+
+		let (status, body) = send("GET /health HTTP/1.1\r\n\r\n");
+		assert_eq!((status, body), (200, json!({ "status": "ok" })));
+
+		let (status, _) = send(&post("/verify/credential", "not json"));
+		assert_eq!(status, 400);
+	}
+}
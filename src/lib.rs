@@ -1,5 +1,6 @@
 #![recursion_limit = "256"]
 #![feature(string_remove_matches)]
+#![feature(once_cell)]
 #![allow(clippy::large_enum_variant)] //StfError: The `Err`-variant returned from this function is very large
 #![allow(clippy::result_large_err)]
 
@@ -9,15 +10,24 @@ pub mod utils;
 
 use crate::primitives::Ed25519Public;
 use primitives::{
-	address::Address32, assertion::Assertion, enclave::Enclave, identity::Identity,
-	keypair::KeyPair, network::Web3Network, signature::validation_data::ValidationData, AccountId,
-	CResult, MrEnclave,
+	address::Address32,
+	assertion::{Assertion, SchemaIndex},
+	enclave::Enclave,
+	identity::Identity,
+	keypair::KeyPair,
+	network::Web3Network,
+	signature::validation_data::ValidationData,
+	trusted_call::TrustedCallSigned,
+	vc::VCSchema,
+	AccountId, BlockAt, BlockHash, CResult, Index, MrEnclave, ShardIdentifier, VCIndex,
 };
 use rsa::RsaPublicKey;
 use service::{
 	getter_trait::WorkerGetters, parachainclient::ParachainRpcClient, workerclient::DirectClient,
 };
 use std::collections::HashMap;
+use substrate_api_client::ExtrinsicReport;
+use utils::vc_registry::VCRegistry;
 
 pub struct Creek {
 	pub parachain_client: ParachainRpcClient,
@@ -58,11 +68,63 @@ pub trait WorkerOp {
 	fn request_vc(&self, assertion: Assertion) -> CResult<()>;
 }
 
+/// Indirect worker operations traits
+/// The trusted call is encrypted with the enclave shielding key and sent to the worker through the
+/// parachain (`Teerex::call_worker`), so no worker endpoint needs to be reachable.
+/// * The extrinsic is signed by `Creek::signer`, which must be a sr25519 keypair.
+/// * The sidechain nonce can't be queried without the worker, so it's provided by the caller.
+/// * The result of the call is reported by the parachain events, see
+///   `ParachainRpcClient::watch_events`.
+pub trait IndirectWorkerOp {
+	fn link_identity_indirect(
+		&self,
+		link_identity: Identity,
+		networks: Vec<Web3Network>,
+		vdata: ValidationData,
+		sidechain_nonce: Index,
+	) -> CResult<ExtrinsicReport<BlockHash>>;
+
+	fn request_vc_indirect(
+		&self,
+		assertion: Assertion,
+		sidechain_nonce: Index,
+	) -> CResult<ExtrinsicReport<BlockHash>>;
+
+	fn submit_indirect_call(
+		&self,
+		shard: ShardIdentifier,
+		trusted_call_signed: TrustedCallSigned,
+	) -> CResult<ExtrinsicReport<BlockHash>>;
+}
+
+/// Delegatee management traits
+/// `add_delegatee` and `remove_delegatee` are only accepted by the parachain when `Creek::signer`
+/// has the delegatee admin role.
+pub trait DelegateeOp {
+	/// All the accounts registered in `IdentityManagement::Delegatee`.
+	fn delegatees(&self) -> CResult<Vec<AccountId>>;
+	fn delegatees_at(&self, at: BlockAt) -> CResult<Vec<AccountId>>;
+	fn is_delegatee(&self, account: &AccountId) -> CResult<bool>;
+	fn add_delegatee(&self, account: AccountId) -> CResult<ExtrinsicReport<BlockHash>>;
+	fn remove_delegatee(&self, account: AccountId) -> CResult<ExtrinsicReport<BlockHash>>;
+}
+
 /// Parachain Operation traits
+/// The `_at` variants read the state as it was at the given block, instead of the latest state.
 pub trait ParachainOp {
 	fn delegatee(&self, account: Address32) -> CResult<Option<()>>;
+	fn delegatee_at(&self, account: Address32, at: BlockAt) -> CResult<Option<()>>;
 	fn enclave_count(&self) -> CResult<Option<u64>>;
+	fn enclave_count_at(&self, at: BlockAt) -> CResult<Option<u64>>;
 	fn enclave(&self, enclave_count: u64) -> CResult<Option<Enclave<AccountId, String>>>;
+	fn enclave_at(
+		&self,
+		enclave_count: u64,
+		at: BlockAt,
+	) -> CResult<Option<Enclave<AccountId, String>>>;
+	/// All the registered enclaves, in registration order.
+	fn enclave_registry(&self) -> CResult<Vec<Enclave<AccountId, String>>>;
+	fn enclave_registry_at(&self, at: BlockAt) -> CResult<Vec<Enclave<AccountId, String>>>;
 	fn get_shard(&self) -> CResult<MrEnclave>;
 	fn get_tee_shielding_pubkey(&self) -> CResult<RsaPublicKey>;
 	fn get_vc_pubkey(&self) -> CResult<Ed25519Public>;
@@ -72,4 +134,23 @@ pub trait ParachainOp {
 	/// while the value performs hex encoding on the obtained Vec<u8> data, which is compatible with
 	/// data parsing in different versions.
 	fn vc_registry(&self) -> CResult<HashMap<String, String>>;
+	fn vc_registry_at(&self, at: BlockAt) -> CResult<HashMap<String, String>>;
+
+	/// Typed version of `vc_registry`, the `VCIndex` is taken from the storage key.
+	/// Entries that can't be decoded as `VCContext` are reported in `VCRegistry::undecodable`
+	/// instead of failing the whole query.
+	fn vc_contexts(&self) -> CResult<VCRegistry>;
+	fn vc_contexts_at(&self, at: BlockAt) -> CResult<VCRegistry>;
+
+	/// The SCALE encoded `VCContext` of a single `VCRegistry` entry, see
+	/// `utils::vc_registry::decode_vc_context`.
+	fn encoded_vc_context(&self, index: VCIndex) -> CResult<Option<Vec<u8>>>;
+	fn encoded_vc_context_at(&self, index: VCIndex, at: BlockAt) -> CResult<Option<Vec<u8>>>;
+
+	/// VC schemas of the `VCManagement::SchemaRegistry`, see
+	/// `utils::vc::verify_vc_registered_schema` to validate a credential against one of them.
+	fn vc_schema(&self, index: SchemaIndex) -> CResult<Option<VCSchema>>;
+	fn vc_schema_at(&self, index: SchemaIndex, at: BlockAt) -> CResult<Option<VCSchema>>;
+	fn vc_schemas(&self) -> CResult<Vec<(SchemaIndex, VCSchema)>>;
+	fn vc_schemas_at(&self, at: BlockAt) -> CResult<Vec<(SchemaIndex, VCSchema)>>;
 }
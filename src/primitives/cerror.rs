@@ -9,5 +9,10 @@ pub enum CError {
 	DecodeJsonError(serde_json::Error),
 	RSAError(rsa::errors::Error),
 	RecvError(std::sync::mpsc::RecvError),
+	/// The pallet is not part of the parachain runtime metadata.
+	PalletNotFound(String),
+	/// (pallet, storage item) is not part of the parachain runtime metadata.
+	StorageNotFound(String, String),
+	MetadataError(String),
 	Other(String),
 }
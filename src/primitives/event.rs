@@ -0,0 +1,251 @@
+// Copyright 2020-2023 Trust Computing GmbH.
+// This file is part of Litentry.
+//
+// Litentry is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Litentry is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Typed events of the `IdentityManagement`, `VCManagement` and `Teerex` pallets.
+//! The field layouts mirror the events emitted by the litentry-parachain runtime, the errors
+//! reported by the TEE are folded into `IMPError` and `VCMPError`.
+
+use super::{
+	aes::AesOutput,
+	assertion::Assertion,
+	cerror::CError,
+	error::{ErrorDetail, IMPError, VCMPError},
+	AccountId, BlockHash, CResult, MrEnclave, ShardIdentifier, SidechainBlockNumber, VCIndex,
+};
+use codec::Decode;
+use sp_core::H256;
+
+pub const IDENTITY_MANAGEMENT_PALLET: &str = "IdentityManagement";
+pub const VC_MANAGEMENT_PALLET: &str = "VCManagement";
+pub const TEEREX_PALLET: &str = "Teerex";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdentityManagementEvent {
+	DelegateeAdded {
+		account: AccountId,
+	},
+	DelegateeRemoved {
+		account: AccountId,
+	},
+	LinkIdentityRequested {
+		shard: ShardIdentifier,
+	},
+	DeactivateIdentityRequested {
+		shard: ShardIdentifier,
+	},
+	ActivateIdentityRequested {
+		shard: ShardIdentifier,
+	},
+	/// `identity` and `id_graph` are encrypted with the request AES key.
+	IdentityLinked {
+		account: AccountId,
+		identity: AesOutput,
+		id_graph: AesOutput,
+		req_ext_hash: H256,
+	},
+	IdentityDeactivated {
+		account: AccountId,
+		identity: AesOutput,
+		req_ext_hash: H256,
+	},
+	IdentityActivated {
+		account: AccountId,
+		identity: AesOutput,
+		req_ext_hash: H256,
+	},
+	/// Any of `LinkIdentityFailed`, `DeactivateIdentityFailed`, `ActivateIdentityFailed`,
+	/// `ImportScheduledEnclaveFailed` or `UnclassifiedError`.
+	Failed {
+		account: Option<AccountId>,
+		error: IMPError,
+		req_ext_hash: Option<H256>,
+	},
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VCManagementEvent {
+	VCRequested {
+		account: AccountId,
+		shard: ShardIdentifier,
+		assertion: Assertion,
+	},
+	VCIssued {
+		account: AccountId,
+		assertion: Assertion,
+		index: VCIndex,
+		req_ext_hash: H256,
+	},
+	VCDisabled {
+		account: AccountId,
+		index: VCIndex,
+	},
+	VCRevoked {
+		account: AccountId,
+		index: VCIndex,
+	},
+	/// Either `RequestVCFailed` or `UnclassifiedError`.
+	Failed {
+		account: Option<AccountId>,
+		error: VCMPError,
+		req_ext_hash: H256,
+	},
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TeerexEvent {
+	AddedEnclave { account: AccountId, url: Vec<u8> },
+	RemovedEnclave { account: AccountId },
+	UpdatedScheduledEnclave { block_number: SidechainBlockNumber, mr_enclave: MrEnclave },
+	RemovedScheduledEnclave { block_number: SidechainBlockNumber },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParachainEvent {
+	IdentityManagement(IdentityManagementEvent),
+	VCManagement(VCManagementEvent),
+	Teerex(TeerexEvent),
+}
+
+/// A decoded event together with the block it was emitted in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParachainEventRecord {
+	pub block_hash: BlockHash,
+	pub event: ParachainEvent,
+}
+
+impl ParachainEvent {
+	/// Decodes the SCALE encoded fields of a `pallet::variant` event.
+	/// Returns `Ok(None)` for the events that are not modelled here.
+	pub fn decode(pallet: &str, variant: &str, fields: &[u8]) -> CResult<Option<Self>> {
+		let event = match pallet {
+			IDENTITY_MANAGEMENT_PALLET =>
+				decode_identity_management_event(variant, fields)?.map(Self::IdentityManagement),
+			VC_MANAGEMENT_PALLET =>
+				decode_vc_management_event(variant, fields)?.map(Self::VCManagement),
+			TEEREX_PALLET => decode_teerex_event(variant, fields)?.map(Self::Teerex),
+			_ => None,
+		};
+
+		Ok(event)
+	}
+}
+
+fn decode_fields<T: Decode>(mut fields: &[u8]) -> CResult<T> {
+	T::decode(&mut fields).map_err(CError::CodecError)
+}
+
+fn decode_identity_management_event(
+	variant: &str,
+	fields: &[u8],
+) -> CResult<Option<IdentityManagementEvent>> {
+	use IdentityManagementEvent::*;
+
+	let failed = |error: fn(ErrorDetail) -> IMPError| -> CResult<IdentityManagementEvent> {
+		let (account, detail, req_ext_hash) =
+			decode_fields::<(Option<AccountId>, ErrorDetail, H256)>(fields)?;
+		Ok(Failed { account, error: error(detail), req_ext_hash: Some(req_ext_hash) })
+	};
+
+	let event = match variant {
+		"DelegateeAdded" => DelegateeAdded { account: decode_fields(fields)? },
+		"DelegateeRemoved" => DelegateeRemoved { account: decode_fields(fields)? },
+		"LinkIdentityRequested" => LinkIdentityRequested { shard: decode_fields(fields)? },
+		"DeactivateIdentityRequested" =>
+			DeactivateIdentityRequested { shard: decode_fields(fields)? },
+		"ActivateIdentityRequested" => ActivateIdentityRequested { shard: decode_fields(fields)? },
+		"IdentityLinked" => {
+			let (account, identity, id_graph, req_ext_hash) = decode_fields(fields)?;
+			IdentityLinked { account, identity, id_graph, req_ext_hash }
+		},
+		"IdentityDeactivated" => {
+			let (account, identity, req_ext_hash) = decode_fields(fields)?;
+			IdentityDeactivated { account, identity, req_ext_hash }
+		},
+		"IdentityActivated" => {
+			let (account, identity, req_ext_hash) = decode_fields(fields)?;
+			IdentityActivated { account, identity, req_ext_hash }
+		},
+		"LinkIdentityFailed" => failed(IMPError::LinkIdentityFailed)?,
+		"DeactivateIdentityFailed" => failed(IMPError::DeactivateIdentityFailed)?,
+		"ActivateIdentityFailed" => failed(IMPError::ActivateIdentityFailed)?,
+		"UnclassifiedError" => failed(IMPError::UnclassifiedError)?,
+		"ImportScheduledEnclaveFailed" => Failed {
+			account: None,
+			error: IMPError::ImportScheduledEnclaveFailed,
+			req_ext_hash: None,
+		},
+		_ => return Ok(None),
+	};
+
+	Ok(Some(event))
+}
+
+fn decode_vc_management_event(variant: &str, fields: &[u8]) -> CResult<Option<VCManagementEvent>> {
+	use VCManagementEvent::*;
+
+	let event = match variant {
+		"VCRequested" => {
+			let (account, shard, assertion) = decode_fields(fields)?;
+			VCRequested { account, shard, assertion }
+		},
+		"VCIssued" => {
+			let (account, assertion, index, req_ext_hash) = decode_fields(fields)?;
+			VCIssued { account, assertion, index, req_ext_hash }
+		},
+		"VCDisabled" => {
+			let (account, index) = decode_fields(fields)?;
+			VCDisabled { account, index }
+		},
+		"VCRevoked" => {
+			let (account, index) = decode_fields(fields)?;
+			VCRevoked { account, index }
+		},
+		"RequestVCFailed" => {
+			let (account, assertion, detail, req_ext_hash) =
+				decode_fields::<(Option<AccountId>, Assertion, ErrorDetail, H256)>(fields)?;
+			Failed { account, error: VCMPError::RequestVCFailed(assertion, detail), req_ext_hash }
+		},
+		"UnclassifiedError" => {
+			let (account, detail, req_ext_hash) =
+				decode_fields::<(Option<AccountId>, ErrorDetail, H256)>(fields)?;
+			Failed { account, error: VCMPError::UnclassifiedError(detail), req_ext_hash }
+		},
+		_ => return Ok(None),
+	};
+
+	Ok(Some(event))
+}
+
+fn decode_teerex_event(variant: &str, fields: &[u8]) -> CResult<Option<TeerexEvent>> {
+	use TeerexEvent::*;
+
+	let event = match variant {
+		"AddedEnclave" => {
+			let (account, url) = decode_fields(fields)?;
+			AddedEnclave { account, url }
+		},
+		"RemovedEnclave" => RemovedEnclave { account: decode_fields(fields)? },
+		"UpdatedScheduledEnclave" => {
+			let (block_number, mr_enclave) = decode_fields(fields)?;
+			UpdatedScheduledEnclave { block_number, mr_enclave }
+		},
+		"RemovedScheduledEnclave" =>
+			RemovedScheduledEnclave { block_number: decode_fields(fields)? },
+		_ => return Ok(None),
+	};
+
+	Ok(Some(event))
+}
@@ -109,21 +109,21 @@ impl Identity {
 			if v.len() == 2 {
 				if v[0] == "substrate" {
 					let handle = decode_hex(v[1])
-						.unwrap()
+						.map_err(|_| "Invalid hex handle")?
 						.as_slice()
 						.try_into()
 						.map_err(|_| "Address32 conversion error")?;
 					return Ok(Identity::Substrate(handle))
 				} else if v[0] == "evm" {
 					let handle = decode_hex(v[1])
-						.unwrap()
+						.map_err(|_| "Invalid hex handle")?
 						.as_slice()
 						.try_into()
 						.map_err(|_| "Address20 conversion error")?;
 					return Ok(Identity::Evm(handle))
 				} else if v[0] == "bitcoin" {
 					let handle = decode_hex(v[1])
-						.unwrap()
+						.map_err(|_| "Invalid hex handle")?
 						.as_slice()
 						.try_into()
 						.map_err(|_| "Address33 conversion error")?;
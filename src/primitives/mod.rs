@@ -22,10 +22,12 @@ pub mod cerror;
 pub mod crypto;
 pub mod enclave;
 pub mod error;
+pub mod event;
 pub mod getter;
 pub mod identity;
 pub mod keypair;
 pub mod network;
+pub mod presentation;
 pub mod rsa_request;
 pub mod signature;
 pub mod stf_error;
@@ -57,6 +59,26 @@ pub type SidechainBlockNumber = u64;
 pub type EnclaveShieldingPubKey = RsaPublicKey;
 
 pub type ParentchainBlockNumber = u32;
+
+/// Selects the parachain block a query reads the state from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockAt {
+	Hash(BlockHash),
+	/// Resolved to the hash of the canonical block at this height.
+	Number(ParentchainBlockNumber),
+}
+
+impl From<BlockHash> for BlockAt {
+	fn from(hash: BlockHash) -> Self {
+		BlockAt::Hash(hash)
+	}
+}
+
+impl From<ParentchainBlockNumber> for BlockAt {
+	fn from(number: ParentchainBlockNumber) -> Self {
+		BlockAt::Number(number)
+	}
+}
 type MaxMetadataLength = ConstU32<128>;
 pub type MetadataOf = BoundedVec<u8, MaxMetadataLength>;
 
@@ -0,0 +1,110 @@
+use super::{
+	identity::Identity, keypair::KeyPair, signature::LitentryMultiSignature, vc::Credential,
+};
+use crate::utils::{
+	hex::{decode_hex, hex_encode},
+	jcs::canonicalize,
+};
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const PRESENTATION_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const PRESENTATION_TYPE: &str = "VerifiablePresentation";
+pub const PRESENTATION_PROOF_TYPE: &str = "LitentryMultiSignature";
+pub const PRESENTATION_PROOF_PURPOSE: &str = "authentication";
+
+/// A W3C Verifiable Presentation: credentials presented by their holder to a relying party.
+/// The holder proves that it controls the subject account by signing the presentation together
+/// with the `challenge` and `domain` of the relying party.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Presentation {
+	#[serde(rename = "@context")]
+	pub context: Vec<String>,
+	#[serde(rename = "type")]
+	pub types: Vec<String>,
+	/// DID of the holder, `did:litentry:substrate:0x...`
+	pub holder: String,
+	pub verifiable_credential: Vec<Credential>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub proof: Option<PresentationProof>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PresentationProof {
+	#[serde(rename = "type")]
+	pub proof_type: String,
+	/// Unix time in milliseconds, like the credential timestamps
+	pub created_timestamp: u64,
+	pub proof_purpose: String,
+	/// Nonce provided by the relying party, prevents the presentation from being replayed.
+	pub challenge: String,
+	/// The relying party the presentation is intended for.
+	pub domain: String,
+	/// DID of the holder
+	pub verification_method: String,
+	/// 0x prefixed hex of the SCALE encoded `LitentryMultiSignature`
+	pub proof_value: String,
+}
+
+impl Presentation {
+	pub fn new(holder: &Identity, credentials: Vec<Credential>) -> Result<Self, String> {
+		Ok(Self {
+			context: vec![PRESENTATION_CONTEXT.to_string()],
+			types: vec![PRESENTATION_TYPE.to_string()],
+			holder: holder.to_did().map_err(|e| format!("{:?}", e))?,
+			verifiable_credential: credentials,
+			proof: None,
+		})
+	}
+
+	/// Presents `credentials` as the account of `keypair`.
+	pub fn sign(
+		keypair: &KeyPair,
+		credentials: Vec<Credential>,
+		challenge: &str,
+		domain: &str,
+		created_timestamp: u64,
+	) -> Result<Self, String> {
+		let holder = Identity::from(keypair.account_id());
+		let mut presentation = Self::new(&holder, credentials)?;
+		presentation.proof = Some(PresentationProof {
+			proof_type: PRESENTATION_PROOF_TYPE.to_string(),
+			created_timestamp,
+			proof_purpose: PRESENTATION_PROOF_PURPOSE.to_string(),
+			challenge: challenge.to_string(),
+			domain: domain.to_string(),
+			verification_method: presentation.holder.clone(),
+			proof_value: String::new(),
+		});
+
+		let signature = keypair.sign(&presentation.signing_payload()?);
+		if let Some(proof) = presentation.proof.as_mut() {
+			proof.proof_value = hex_encode(&signature.encode());
+		}
+
+		Ok(presentation)
+	}
+
+	/// The JCS canonical presentation without `proofValue`, so that the challenge and domain are
+	/// signed along with the credentials, whatever the member order of the presented JSON.
+	pub fn signing_payload(&self) -> Result<Vec<u8>, String> {
+		let mut unsigned = serde_json::to_value(self).map_err(|e| format!("{:?}", e))?;
+		if let Some(proof) = unsigned.get_mut("proof").and_then(Value::as_object_mut) {
+			proof.remove("proofValue");
+		}
+
+		Ok(canonicalize(&unsigned).into_bytes())
+	}
+
+	pub fn holder_identity(&self) -> Option<Identity> {
+		Identity::from_did(&self.holder).ok()
+	}
+
+	pub fn signature(&self) -> Option<LitentryMultiSignature> {
+		let proof_value = decode_hex(&self.proof.as_ref()?.proof_value).ok()?;
+		LitentryMultiSignature::decode(&mut proof_value.as_slice()).ok()
+	}
+}
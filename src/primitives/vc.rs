@@ -14,11 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Litentry.  If not, see <https://www.gnu.org/licenses/>.
 
-use super::{aes::AesOutput, assertion::Assertion, AccountId};
+use super::{
+	aes::AesOutput,
+	assertion::{Assertion, SchemaContentString, SchemaIdString},
+	AccountId,
+};
 use codec::{Decode, Encode};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sp_core::H256;
+use std::collections::BTreeMap;
 
 /// Ed25519 Signature 2018, W3C, 23 July 2021, https://w3c-ccg.github.io/lds-ed25519-2018
 /// May be registered in Linked Data Cryptographic Suite Registry, W3C, 29 December 2020
@@ -40,6 +46,10 @@ pub struct DataSource {
 	pub data_provider_id: u32,
 	/// Endpoint of the data provider
 	pub data_provider: String,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
@@ -49,6 +59,10 @@ pub struct Issuer {
 	pub id: String,
 	pub name: String,
 	pub mrenclave: String,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
@@ -62,7 +76,6 @@ pub struct CredentialSubject {
 	/// (Optional) Some externally provided identifiers
 	pub tag: Vec<String>,
 	/// (Optional) Data source definitions for trusted data providers
-	#[serde(skip_deserializing)]
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub data_source: Option<Vec<DataSource>>,
 	/// Several sets of assertions.
@@ -74,6 +87,10 @@ pub struct CredentialSubject {
 	pub values: Vec<bool>,
 	/// The extrinsic on Parentchain for credential verification purpose
 	pub endpoint: String,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Debug, PartialEq, Eq, TypeInfo, Copy, Clone)]
@@ -119,6 +136,10 @@ pub struct CredentialSchema {
 	/// The schema type, generally it is
 	#[serde(rename = "type")]
 	pub types: String,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
@@ -135,8 +156,17 @@ pub struct Proof {
 	pub proof_value: String,
 	/// The public key from Issuer
 	pub verification_method: String,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
 }
 
+/// A credential as issued by the worker.
+/// A parsed credential only serialises back to the JSON it was parsed from when it has no `extra`
+/// members, while its proof and `VCRegistry` hash are over that JSON. Stored or presented
+/// credentials have to be verified from their JSON: `verify_vc_raw_with`, `Verifier::verify_raw`
+/// or `verify_presentation_raw`.
 #[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
 #[serde(rename_all = "camelCase")]
 pub struct Credential {
@@ -163,10 +193,142 @@ pub struct Credential {
 	// #[serde(skip_serializing)]
 	pub proof: Option<Proof>,
 
-	#[serde(skip_deserializing)]
-	#[serde(skip_serializing)]
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub credential_schema: Option<CredentialSchema>,
+
+	/// Members this version doesn't know about, kept so that the credential round-trips. They are
+	/// serialised after the known ones, in key order.
+	#[serde(flatten)]
+	#[codec(skip)]
+	pub extra: BTreeMap<String, Value>,
+}
+
+pub const CREDENTIALS_V1_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+/// `credentialStatus` type of a credential whose status is kept in `VCManagement::VCRegistry`
+pub const VC_REGISTRY_STATUS_TYPE: &str = "VCRegistry";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialStatusEntry {
+	/// The `VCIndex` the status is registered at, same as the credential `id`
+	pub id: String,
+	#[serde(rename = "type")]
+	pub status_type: String,
+}
+
+/// W3C Verifiable Credentials Data Model 2.0 layout of a `Credential`.
+/// The conversion from a `Credential` and back is lossless: the timestamps are written with
+/// millisecond precision and `credentialStatus` is derived from the `id`. The `proof` is kept as
+/// is, it still signs the `Credential` layout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialV2 {
+	#[serde(rename = "@context")]
+	pub context: Vec<String>,
+	pub id: String,
+	#[serde(rename = "type")]
+	pub types: Vec<CredentialType>,
+	pub credential_subject: CredentialSubject,
+	pub issuer: Issuer,
+	/// `xsd:dateTime`, e.g. `2023-10-07T07:23:58.000Z`
+	pub valid_from: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub valid_until: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub credential_status: Option<CredentialStatusEntry>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub credential_schema: Option<CredentialSchema>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub proof: Option<Proof>,
+	/// Unknown members, see `Credential::extra`
+	#[serde(flatten)]
+	pub extra: BTreeMap<String, Value>,
+}
+
+impl CredentialV2 {
+	pub fn is_v2(context: &[String]) -> bool {
+		context.iter().any(|context| context == CREDENTIALS_V2_CONTEXT)
+	}
+}
+
+impl TryFrom<Credential> for CredentialV2 {
+	type Error = String;
+
+	fn try_from(vc: Credential) -> Result<Self, Self::Error> {
+		if CredentialV2::is_v2(&vc.context) {
+			return Err("The credential already has the v2 context".to_string())
+		}
+
+		Ok(Self {
+			context: swap_context(&vc.context, CREDENTIALS_V1_CONTEXT, CREDENTIALS_V2_CONTEXT),
+			credential_status: Some(CredentialStatusEntry {
+				id: vc.id.clone(),
+				status_type: VC_REGISTRY_STATUS_TYPE.to_string(),
+			}),
+			id: vc.id,
+			types: vc.types,
+			credential_subject: vc.credential_subject,
+			issuer: vc.issuer,
+			valid_from: millis_to_datetime(vc.issuance_timestamp)?,
+			valid_until: vc.expiration_timestamp.map(millis_to_datetime).transpose()?,
+			credential_schema: vc.credential_schema,
+			proof: vc.proof,
+			extra: vc.extra,
+		})
+	}
+}
+
+impl TryFrom<CredentialV2> for Credential {
+	type Error = String;
+
+	fn try_from(vc: CredentialV2) -> Result<Self, Self::Error> {
+		if vc.context.iter().any(|context| context == CREDENTIALS_V1_CONTEXT) {
+			return Err("The credential has both the v1 and v2 contexts".to_string())
+		}
+		if let Some(status) = &vc.credential_status {
+			if status.status_type != VC_REGISTRY_STATUS_TYPE || status.id != vc.id {
+				return Err(format!("Unsupported credentialStatus {:?}", status))
+			}
+		}
+
+		Ok(Self {
+			context: swap_context(&vc.context, CREDENTIALS_V2_CONTEXT, CREDENTIALS_V1_CONTEXT),
+			id: vc.id,
+			types: vc.types,
+			credential_subject: vc.credential_subject,
+			issuer: vc.issuer,
+			issuance_timestamp: datetime_to_millis(&vc.valid_from)?,
+			expiration_timestamp: vc.valid_until.as_deref().map(datetime_to_millis).transpose()?,
+			proof: vc.proof,
+			credential_schema: vc.credential_schema,
+			extra: vc.extra,
+		})
+	}
+}
+
+fn swap_context(context: &[String], from: &str, to: &str) -> Vec<String> {
+	context
+		.iter()
+		.map(|context| if context == from { to.to_string() } else { context.clone() })
+		.collect()
+}
+
+fn millis_to_datetime(timestamp: u64) -> Result<String, String> {
+	let datetime = i64::try_from(timestamp)
+		.ok()
+		.and_then(chrono::NaiveDateTime::from_timestamp_millis)
+		.ok_or_else(|| format!("Invalid timestamp {}", timestamp))?;
+
+	Ok(datetime.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+}
+
+/// Any RFC 3339 datetime is accepted, sub-millisecond precision is dropped.
+fn datetime_to_millis(datetime: &str) -> Result<u64, String> {
+	let datetime = chrono::DateTime::parse_from_rfc3339(datetime)
+		.map_err(|e| format!("Invalid datetime {}: {:?}", datetime, e))?;
+
+	u64::try_from(datetime.timestamp_millis()).map_err(|_| format!("Invalid datetime {}", datetime))
 }
 
 // #[derive(
@@ -201,6 +363,30 @@ impl VCContext {
 	}
 }
 
+/// A VC schema registered in the `VCManagement::SchemaRegistry`.
+#[derive(Clone, Eq, PartialEq, Debug, Encode, Decode)]
+pub struct VCSchema {
+	// the schema id, which is the `CredentialSchema::id` of the credentials using it
+	pub id: SchemaIdString,
+	// who registered the schema
+	pub author: AccountId,
+	// the JSON schema of the credentials
+	pub content: SchemaContentString,
+	// status of the schema
+	pub status: Status,
+}
+
+impl VCSchema {
+	pub fn id(&self) -> String {
+		String::from_utf8_lossy(&self.id).to_string()
+	}
+
+	/// The content parsed as a JSON schema document.
+	pub fn content_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+		serde_json::from_slice(&self.content)
+	}
+}
+
 #[derive(Encode, Decode, Clone, Debug, PartialEq, Eq, TypeInfo)]
 pub enum ErrorDetail {
 	// error when importing the parentchain blocks and executing indirect calls
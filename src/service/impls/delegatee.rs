@@ -0,0 +1,60 @@
+use crate::{
+	primitives::{address::Address32, cerror::CError, AccountId, BlockAt, BlockHash, CResult},
+	Creek, DelegateeOp, ParachainOp,
+};
+use substrate_api_client::{ExtrinsicReport, GetStorage};
+
+const IDENTITY_PALLET_NAME: &str = "IdentityManagement";
+
+impl DelegateeOp for Creek {
+	fn delegatees(&self) -> CResult<Vec<AccountId>> {
+		delegatees(self, None)
+	}
+
+	fn delegatees_at(&self, at: BlockAt) -> CResult<Vec<AccountId>> {
+		delegatees(self, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn is_delegatee(&self, account: &AccountId) -> CResult<bool> {
+		Ok(self.delegatee(Address32::from(account.clone()))?.is_some())
+	}
+
+	fn add_delegatee(&self, account: AccountId) -> CResult<ExtrinsicReport<BlockHash>> {
+		self.parachain_client
+			.submit_call(IDENTITY_PALLET_NAME, "add_delegatee", account)
+	}
+
+	fn remove_delegatee(&self, account: AccountId) -> CResult<ExtrinsicReport<BlockHash>> {
+		self.parachain_client
+			.submit_call(IDENTITY_PALLET_NAME, "remove_delegatee", account)
+	}
+}
+
+fn delegatees(creek: &Creek, at: Option<BlockHash>) -> CResult<Vec<AccountId>> {
+	let storage_key = creek
+		.parachain_client
+		.storage_map_key_prefix(IDENTITY_PALLET_NAME, "Delegatee")?;
+	let keys = creek
+		.parachain_client
+		.api
+		.get_keys(storage_key, at)
+		.map_err(|_| CError::APIError)?
+		.unwrap_or_default();
+
+	keys.iter()
+		.map(|key| {
+			let storage_key = hex::decode(&key[2..]).map_err(CError::FromHexError)?;
+			delegatee_from_storage_key(&storage_key)
+				.ok_or_else(|| CError::Other(format!("Invalid Delegatee storage key: {}", key)))
+		})
+		.collect()
+}
+
+/// `Delegatee` is a `Blake2_128Concat` map, so the account is the trailing 32 bytes of each key.
+pub fn delegatee_from_storage_key(storage_key: &[u8]) -> Option<AccountId> {
+	storage_key
+		.len()
+		.checked_sub(32)
+		.and_then(|start| Address32::try_from(&storage_key[start..]).ok())
+		.map(AccountId::from)
+}
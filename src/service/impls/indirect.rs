@@ -0,0 +1,67 @@
+use crate::{
+	primitives::{
+		assertion::Assertion, identity::Identity, network::Web3Network, rsa_request::RsaRequest,
+		signature::validation_data::ValidationData, trusted_call::TrustedCallSigned, BlockHash,
+		CResult, Index, ShardIdentifier,
+	},
+	service::impls::worker_inner::{link_identity_call, request_vc_call},
+	utils::crypto::encrypt_with_tee_shielding_pubkey,
+	Creek, IndirectWorkerOp, ParachainOp,
+};
+use codec::Encode;
+use log::info;
+use substrate_api_client::ExtrinsicReport;
+
+const TEEREX_PALLET_NAME: &str = "Teerex";
+
+impl IndirectWorkerOp for Creek {
+	fn link_identity_indirect(
+		&self,
+		link_identity: Identity,
+		networks: Vec<Web3Network>,
+		vdata: ValidationData,
+		sidechain_nonce: Index,
+	) -> CResult<ExtrinsicReport<BlockHash>> {
+		let trusted_call = link_identity_call(self, link_identity, networks, vdata);
+
+		let mrenclave = self.get_shard()?;
+		let shard = ShardIdentifier::from(mrenclave);
+		let signed_call = trusted_call.sign(&self.signer, sidechain_nonce, &mrenclave, &shard);
+
+		self.submit_indirect_call(shard, signed_call)
+	}
+
+	fn request_vc_indirect(
+		&self,
+		assertion: Assertion,
+		sidechain_nonce: Index,
+	) -> CResult<ExtrinsicReport<BlockHash>> {
+		let trusted_call = request_vc_call(self, assertion);
+
+		let mrenclave = self.get_shard()?;
+		let shard = ShardIdentifier::from(mrenclave);
+		let signed_call = trusted_call.sign(&self.signer, sidechain_nonce, &mrenclave, &shard);
+
+		self.submit_indirect_call(shard, signed_call)
+	}
+
+	fn submit_indirect_call(
+		&self,
+		shard: ShardIdentifier,
+		trusted_call_signed: TrustedCallSigned,
+	) -> CResult<ExtrinsicReport<BlockHash>> {
+		let shielding_pubkey = self.get_tee_shielding_pubkey()?;
+		let operation_call_encrypted = encrypt_with_tee_shielding_pubkey(
+			&shielding_pubkey,
+			&trusted_call_signed.into_trusted_operation(false).encode(),
+		);
+
+		let request = RsaRequest::new(shard, operation_call_encrypted);
+		let report =
+			self.parachain_client.submit_call(TEEREX_PALLET_NAME, "call_worker", request)?;
+
+		info!("[INDIRECT CALL]: {:?}", report.extrinsic_hash);
+
+		Ok(report)
+	}
+}
@@ -18,7 +18,9 @@ use crate::{
 	Creek,
 };
 
+pub mod delegatee;
 pub mod getter;
+pub mod indirect;
 pub mod parachain;
 pub mod vdata;
 pub mod worker;
@@ -2,39 +2,58 @@ use std::collections::HashMap;
 
 use crate::{
 	primitives::{
-		address::Address32, cerror::CError, crypto::RsaPublicKeyGenerator, enclave::Enclave,
-		AccountId, CResult, MrEnclave,
+		address::Address32, assertion::SchemaIndex, cerror::CError, crypto::RsaPublicKeyGenerator,
+		enclave::Enclave, vc::VCSchema, AccountId, BlockAt, BlockHash, CResult, MrEnclave, VCIndex,
+	},
+	utils::{
+		address::vec_to_u8_array,
+		vc_registry::{vc_index_from_storage_key, VCRegistry},
 	},
-	utils::address::vec_to_u8_array,
 	Creek, ParachainOp,
 };
 use rsa::RsaPublicKey;
 use sp_core::{ed25519::Public as Ed25519Public, hexdisplay::HexDisplay};
 use substrate_api_client::{ac_primitives::StorageKey, GetStorage};
 
-const TEEREX_STORAGE_PREFIX_NAME: &str = "Teerex";
-const IDENTITY_PALLET_NAME: &str = "IdentityMangement";
+const TEEREX_PALLET_NAME: &str = "Teerex";
+const IDENTITY_PALLET_NAME: &str = "IdentityManagement";
+const VC_MANAGEMENT_PALLET_NAME: &str = "VCManagement";
 
 impl ParachainOp for Creek {
 	fn delegatee(&self, account: Address32) -> CResult<Option<()>> {
-		self.parachain_client
-			.api
-			.get_storage_map(IDENTITY_PALLET_NAME, "Delegatee", account, None)
-			.map_err(|_| CError::APIError)
+		delegatee(self, account, None)
+	}
+
+	fn delegatee_at(&self, account: Address32, at: BlockAt) -> CResult<Option<()>> {
+		delegatee(self, account, Some(self.parachain_client.block_hash(at)?))
 	}
 
 	fn enclave_count(&self) -> CResult<Option<u64>> {
-		self.parachain_client
-			.api
-			.get_storage(TEEREX_STORAGE_PREFIX_NAME, "EnclaveCount", None)
-			.map_err(|_| CError::APIError)
+		enclave_count(self, None)
+	}
+
+	fn enclave_count_at(&self, at: BlockAt) -> CResult<Option<u64>> {
+		enclave_count(self, Some(self.parachain_client.block_hash(at)?))
 	}
 
 	fn enclave(&self, enclave_count: u64) -> CResult<Option<Enclave<AccountId, String>>> {
-		self.parachain_client
-			.api
-			.get_storage_map(TEEREX_STORAGE_PREFIX_NAME, "EnclaveRegistry", enclave_count, None)
-			.map_err(|_| CError::APIError)
+		enclave(self, enclave_count, None)
+	}
+
+	fn enclave_at(
+		&self,
+		enclave_count: u64,
+		at: BlockAt,
+	) -> CResult<Option<Enclave<AccountId, String>>> {
+		enclave(self, enclave_count, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn enclave_registry(&self) -> CResult<Vec<Enclave<AccountId, String>>> {
+		enclave_registry(self, None)
+	}
+
+	fn enclave_registry_at(&self, at: BlockAt) -> CResult<Vec<Enclave<AccountId, String>>> {
+		enclave_registry(self, Some(self.parachain_client.block_hash(at)?))
 	}
 
 	fn get_tee_shielding_pubkey(&self) -> CResult<RsaPublicKey> {
@@ -89,32 +108,184 @@ impl ParachainOp for Creek {
 	}
 
 	fn vc_registry(&self) -> CResult<HashMap<String, String>> {
-		let vcregistry_encoded_keys =
-			"b8806b89e4f9af656f87b35e6112ee1bda2e7b4c5a367debe17c26748ec6b3e6";
-		let storage_key = hex::decode(vcregistry_encoded_keys).map_err(CError::FromHexError)?;
-		let keys = self
-			.parachain_client
-			.api
-			.get_keys(StorageKey(storage_key), None)
-			.map_err(|_| CError::APIError)?;
-
-		let mut vc_registry: HashMap<String, String> = HashMap::new();
-		if let Some(keys) = keys {
-			for key in keys {
-				let storage_key = hex::decode(&key[2..]).map_err(CError::FromHexError)?;
-				let vc_context = self
-					.parachain_client
-					.api
-					.get_opaque_storage_by_key(StorageKey(storage_key), None)
-					.map_err(|e| CError::Other(format!("{:?}", e)))?;
-
-				if let Some(context) = vc_context {
-					let context_in_hex = hex::encode(context);
-					vc_registry.insert(key, context_in_hex);
-				}
+		vc_registry(self, None)
+	}
+
+	fn vc_registry_at(&self, at: BlockAt) -> CResult<HashMap<String, String>> {
+		vc_registry(self, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn vc_contexts(&self) -> CResult<VCRegistry> {
+		vc_contexts(self, None)
+	}
+
+	fn vc_contexts_at(&self, at: BlockAt) -> CResult<VCRegistry> {
+		vc_contexts(self, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn encoded_vc_context(&self, index: VCIndex) -> CResult<Option<Vec<u8>>> {
+		encoded_vc_context(self, index, None)
+	}
+
+	fn encoded_vc_context_at(&self, index: VCIndex, at: BlockAt) -> CResult<Option<Vec<u8>>> {
+		encoded_vc_context(self, index, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn vc_schema(&self, index: SchemaIndex) -> CResult<Option<VCSchema>> {
+		vc_schema(self, index, None)
+	}
+
+	fn vc_schema_at(&self, index: SchemaIndex, at: BlockAt) -> CResult<Option<VCSchema>> {
+		vc_schema(self, index, Some(self.parachain_client.block_hash(at)?))
+	}
+
+	fn vc_schemas(&self) -> CResult<Vec<(SchemaIndex, VCSchema)>> {
+		vc_schemas(self, None)
+	}
+
+	fn vc_schemas_at(&self, at: BlockAt) -> CResult<Vec<(SchemaIndex, VCSchema)>> {
+		vc_schemas(self, Some(self.parachain_client.block_hash(at)?))
+	}
+}
+
+fn delegatee(creek: &Creek, account: Address32, at: Option<BlockHash>) -> CResult<Option<()>> {
+	let key = creek
+		.parachain_client
+		.storage_map_key(IDENTITY_PALLET_NAME, "Delegatee", account)?;
+	creek.parachain_client.get_storage_by_key(key, at)
+}
+
+fn enclave_count(creek: &Creek, at: Option<BlockHash>) -> CResult<Option<u64>> {
+	let key = creek.parachain_client.storage_value_key(TEEREX_PALLET_NAME, "EnclaveCount")?;
+	creek.parachain_client.get_storage_by_key(key, at)
+}
+
+fn enclave(
+	creek: &Creek,
+	enclave_count: u64,
+	at: Option<BlockHash>,
+) -> CResult<Option<Enclave<AccountId, String>>> {
+	let key = creek.parachain_client.storage_map_key(
+		TEEREX_PALLET_NAME,
+		"EnclaveRegistry",
+		enclave_count,
+	)?;
+	creek.parachain_client.get_storage_by_key(key, at)
+}
+
+/// `EnclaveRegistry` is indexed from 1 to `EnclaveCount`.
+fn enclave_registry(
+	creek: &Creek,
+	at: Option<BlockHash>,
+) -> CResult<Vec<Enclave<AccountId, String>>> {
+	let enclave_count = enclave_count(creek, at)?.unwrap_or_default();
+
+	let mut enclaves = vec![];
+	for index in 1..=enclave_count {
+		if let Some(enclave) = enclave(creek, index, at)? {
+			enclaves.push(enclave);
+		}
+	}
+
+	Ok(enclaves)
+}
+
+fn vc_registry(creek: &Creek, at: Option<BlockHash>) -> CResult<HashMap<String, String>> {
+	let vc_registry = vc_registry_storage(creek, at)?
+		.into_iter()
+		.map(|(key, context)| (key, hex::encode(context)))
+		.collect();
+
+	Ok(vc_registry)
+}
+
+fn vc_contexts(creek: &Creek, at: Option<BlockHash>) -> CResult<VCRegistry> {
+	let mut vc_registry = VCRegistry::default();
+	for (key, context) in vc_registry_storage(creek, at)? {
+		let storage_key = hex::decode(&key[2..]).map_err(CError::FromHexError)?;
+		let vc_index = vc_index_from_storage_key(&storage_key)
+			.ok_or_else(|| CError::Other(format!("Invalid VCRegistry storage key: {}", key)))?;
+
+		vc_registry.insert_encoded(vc_index, &context);
+	}
+
+	Ok(vc_registry)
+}
+
+/// All the raw `VCRegistry` entries, keyed by the hex encoded storage key.
+fn vc_registry_storage(creek: &Creek, at: Option<BlockHash>) -> CResult<Vec<(String, Vec<u8>)>> {
+	let storage_key = creek
+		.parachain_client
+		.storage_map_key_prefix(VC_MANAGEMENT_PALLET_NAME, "VCRegistry")?;
+	let keys = creek
+		.parachain_client
+		.api
+		.get_keys(storage_key, at)
+		.map_err(|_| CError::APIError)?;
+
+	let mut vc_registry = vec![];
+	if let Some(keys) = keys {
+		for key in keys {
+			let storage_key = hex::decode(&key[2..]).map_err(CError::FromHexError)?;
+			let vc_context = creek
+				.parachain_client
+				.api
+				.get_opaque_storage_by_key(StorageKey(storage_key), at)
+				.map_err(|e| CError::Other(format!("{:?}", e)))?;
+
+			if let Some(context) = vc_context {
+				vc_registry.push((key, context));
 			}
 		}
+	}
+
+	Ok(vc_registry)
+}
 
-		Ok(vc_registry)
+fn encoded_vc_context(
+	creek: &Creek,
+	index: VCIndex,
+	at: Option<BlockHash>,
+) -> CResult<Option<Vec<u8>>> {
+	let key =
+		creek
+			.parachain_client
+			.storage_map_key(VC_MANAGEMENT_PALLET_NAME, "VCRegistry", index)?;
+	creek
+		.parachain_client
+		.api
+		.get_opaque_storage_by_key(key, at)
+		.map_err(|e| CError::Other(format!("{:?}", e)))
+}
+
+fn vc_schema(
+	creek: &Creek,
+	index: SchemaIndex,
+	at: Option<BlockHash>,
+) -> CResult<Option<VCSchema>> {
+	let key = creek.parachain_client.storage_map_key(
+		VC_MANAGEMENT_PALLET_NAME,
+		"SchemaRegistry",
+		index,
+	)?;
+	creek.parachain_client.get_storage_by_key(key, at)
+}
+
+/// `SchemaRegistryIndex` is the index of the next schema, the registered ones are indexed from 0.
+/// Revoked schemas are removed from the registry and skipped.
+fn vc_schemas(creek: &Creek, at: Option<BlockHash>) -> CResult<Vec<(SchemaIndex, VCSchema)>> {
+	let key = creek
+		.parachain_client
+		.storage_value_key(VC_MANAGEMENT_PALLET_NAME, "SchemaRegistryIndex")?;
+	let next_index: SchemaIndex =
+		creek.parachain_client.get_storage_by_key(key, at)?.unwrap_or_default();
+
+	let mut schemas = vec![];
+	for index in 0..next_index {
+		if let Some(schema) = vc_schema(creek, index, at)? {
+			schemas.push((index, schema));
+		}
 	}
+
+	Ok(schemas)
 }
@@ -36,17 +36,7 @@ impl LinkIdentityInner for Creek {
 		vdata: ValidationData,
 	) -> CResult<TrustedCallSigned> {
 		let signer_acccount = self.signer.account_id();
-		let primary_identity = Identity::from(signer_acccount.clone());
-
-		let trusted_call = TrustedCall::link_identity(
-			primary_identity.clone(),
-			primary_identity,
-			link_identity,
-			vdata,
-			networks,
-			None,
-			Default::default(),
-		);
+		let trusted_call = link_identity_call(self, link_identity, networks, vdata);
 
 		let mrenclave = self.state_get_mrenclave()?;
 		let sidechain_nonce = self.author_get_next_nonce(
@@ -63,20 +53,7 @@ impl LinkIdentityInner for Creek {
 		assertion: Assertion,
 	) -> CResult<TrustedCallSigned> {
 		let signer_acccount = self.signer.account_id();
-		let primary_identity = Identity::from(signer_acccount.clone());
-
-		let key = Some([
-			1_u8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-			1, 1, 11, 1,
-		]);
-
-		let trusted_call = TrustedCall::request_vc(
-			primary_identity.clone(),
-			primary_identity,
-			assertion,
-			key,
-			Default::default(),
-		);
+		let trusted_call = request_vc_call(self, assertion);
 
 		let mrenclave = self.state_get_mrenclave()?;
 		let sidechain_nonce = self.author_get_next_nonce(
@@ -87,3 +64,41 @@ impl LinkIdentityInner for Creek {
 		Ok(signed_call)
 	}
 }
+
+/// The `TrustedCall` linking `link_identity` to the signer's identity.
+pub(crate) fn link_identity_call(
+	creek: &Creek,
+	link_identity: Identity,
+	networks: Vec<Web3Network>,
+	vdata: ValidationData,
+) -> TrustedCall {
+	let primary_identity = Identity::from(creek.signer.account_id());
+
+	TrustedCall::link_identity(
+		primary_identity.clone(),
+		primary_identity,
+		link_identity,
+		vdata,
+		networks,
+		None,
+		Default::default(),
+	)
+}
+
+/// The `TrustedCall` requesting a VC of `assertion` for the signer's identity.
+pub(crate) fn request_vc_call(creek: &Creek, assertion: Assertion) -> TrustedCall {
+	let primary_identity = Identity::from(creek.signer.account_id());
+
+	let key = Some([
+		1_u8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+		1, 11, 1,
+	]);
+
+	TrustedCall::request_vc(
+		primary_identity.clone(),
+		primary_identity,
+		assertion,
+		key,
+		Default::default(),
+	)
+}
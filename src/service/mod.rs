@@ -16,7 +16,11 @@ impl CreekExplorer for Creek {
 		worker_endpoint: &str,
 		signer: KeyPair,
 	) -> CResult<Creek> {
-		let parachain_client = ParachainRpcClient::new(parachain_endpoint)?;
+		let mut parachain_client = ParachainRpcClient::new(parachain_endpoint)?;
+		// An ed25519 signer can still sign trusted calls, only the indirect calls are unavailable.
+		if matches!(signer, KeyPair::Sr25519(_)) {
+			parachain_client.set_signer(&signer)?;
+		}
 		let worker_client = DirectClient::new(worker_endpoint.to_string());
 
 		Ok(Self { parachain_client, worker_client, signer })
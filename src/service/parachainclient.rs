@@ -1,14 +1,167 @@
-use crate::primitives::{cerror::CError, CResult};
-use substrate_api_client::{ac_primitives::AssetRuntimeConfig, rpc::TungsteniteRpcClient, Api};
+use crate::{
+	primitives::{
+		cerror::CError,
+		event::{ParachainEvent, ParachainEventRecord},
+		keypair::KeyPair,
+		BlockAt, BlockHash, CResult,
+	},
+	utils::storage::{call_index, storage_entry, StorageEntry},
+};
+use codec::{Decode, Encode};
+use frame_metadata::RuntimeMetadataPrefixed;
+use log::*;
+use sp_core::Bytes;
+use std::sync::mpsc::Sender as MpscSender;
+use substrate_api_client::{
+	ac_primitives::{AssetRuntimeConfig, ExtrinsicSigner, RpcParams, StorageKey},
+	rpc::{Request, TungsteniteRpcClient},
+	Api, ExtrinsicReport, GetChainInfo, GetStorage, SubmitAndWatch, SubscribeEvents, XtStatus,
+};
 
 pub struct ParachainRpcClient {
 	pub api: Api<AssetRuntimeConfig, TungsteniteRpcClient>,
+	/// Runtime metadata of the parachain, all the storage keys are built from it.
+	pub metadata: RuntimeMetadataPrefixed,
 }
 
 impl ParachainRpcClient {
 	pub fn new(endpoint: &str) -> CResult<Self> {
 		let client = TungsteniteRpcClient::new(endpoint, 100).map_err(|_| CError::APIError)?;
+		let metadata_bytes: Bytes = client
+			.request("state_getMetadata", RpcParams::new())
+			.map_err(|e| CError::Other(format!("Get parachain metadata error: {:?}", e)))?;
+		let metadata = RuntimeMetadataPrefixed::decode(&mut metadata_bytes.0.as_slice())
+			.map_err(CError::CodecError)?;
+
 		let api = Api::<AssetRuntimeConfig, _>::new(client).unwrap();
-		Ok(Self { api })
+		Ok(Self { api, metadata })
+	}
+
+	/// Extrinsics can only be signed with a sr25519 key, an ed25519 signer is rejected and the
+	/// previous signer is kept.
+	pub fn set_signer(&mut self, signer: &KeyPair) -> CResult<()> {
+		match signer {
+			KeyPair::Sr25519(pair) => {
+				self.api.set_signer(ExtrinsicSigner::<AssetRuntimeConfig>::new(*pair.clone()));
+				Ok(())
+			},
+			KeyPair::Ed25519(_) =>
+				Err(CError::Other("Extrinsics require a sr25519 signer".to_string())),
+		}
+	}
+
+	pub fn block_hash(&self, at: BlockAt) -> CResult<BlockHash> {
+		match at {
+			BlockAt::Hash(hash) => Ok(hash),
+			BlockAt::Number(number) => self
+				.api
+				.get_block_hash(Some(number))
+				.map_err(|e| CError::Other(format!("Get block hash error: {:?}", e)))?
+				.ok_or_else(|| CError::Other(format!("Block #{} not found", number))),
+		}
+	}
+
+	pub fn storage_entry(&self, pallet: &str, item: &str) -> CResult<StorageEntry> {
+		storage_entry(&self.metadata, pallet, item)
+	}
+
+	pub fn storage_value_key(&self, pallet: &str, item: &str) -> CResult<StorageKey> {
+		self.storage_entry(pallet, item)?.value_key().map(StorageKey)
+	}
+
+	pub fn storage_map_key<K: Encode>(
+		&self,
+		pallet: &str,
+		item: &str,
+		key: K,
+	) -> CResult<StorageKey> {
+		self.storage_entry(pallet, item)?.map_key(&[key.encode()]).map(StorageKey)
+	}
+
+	/// The prefix shared by all the keys of a storage map, used to iterate its keys.
+	pub fn storage_map_key_prefix(&self, pallet: &str, item: &str) -> CResult<StorageKey> {
+		Ok(StorageKey(self.storage_entry(pallet, item)?.prefix()))
+	}
+
+	pub fn get_storage_by_key<V: Decode>(
+		&self,
+		key: StorageKey,
+		at: Option<BlockHash>,
+	) -> CResult<Option<V>> {
+		self.api
+			.get_opaque_storage_by_key(key, at)
+			.map_err(|e| CError::Other(format!("{:?}", e)))?
+			.map(|value| V::decode(&mut value.as_slice()).map_err(CError::CodecError))
+			.transpose()
+	}
+
+	/// Subscribes to the finalized events and forwards the `IdentityManagement`, `VCManagement`
+	/// and `Teerex` ones to `sender`.
+	/// Blocks until the subscription ends or the receiver is dropped, so it's usually run in its
+	/// own thread. Events which can't be decoded are logged and skipped.
+	pub fn watch_events(&self, sender: MpscSender<ParachainEventRecord>) -> CResult<()> {
+		let mut subscription = self
+			.api
+			.subscribe_events()
+			.map_err(|e| CError::Other(format!("Subscribe events error: {:?}", e)))?;
+
+		while let Some(events) = subscription.next_events_from_metadata() {
+			let events = events.map_err(|e| CError::Other(format!("Get events error: {:?}", e)))?;
+			let block_hash = events.block_hash();
+
+			for event in events.iter() {
+				let event = match event {
+					Ok(event) => event,
+					Err(e) => {
+						warn!("Skip undecodable event in block {:?}: {:?}", block_hash, e);
+						continue
+					},
+				};
+
+				let decoded = ParachainEvent::decode(
+					event.pallet_name(),
+					event.variant_name(),
+					event.field_bytes(),
+				);
+				match decoded {
+					Ok(Some(event)) =>
+						if sender.send(ParachainEventRecord { block_hash, event }).is_err() {
+							debug!("Event receiver dropped, stop watching events");
+							return Ok(())
+						},
+					Ok(None) => {},
+					Err(e) => warn!(
+						"Skip {}::{} event in block {:?}: {:?}",
+						event.pallet_name(),
+						event.variant_name(),
+						block_hash,
+						e
+					),
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Signs `pallet::call(args)` with the signer set by `set_signer`, submits it and waits until
+	/// it is included in a block.
+	pub fn submit_call<A: Encode>(
+		&self,
+		pallet: &str,
+		call: &str,
+		args: A,
+	) -> CResult<ExtrinsicReport<BlockHash>> {
+		if self.api.signer().is_none() {
+			return Err(CError::Other("Extrinsics require a sr25519 signer".to_string()))
+		}
+
+		let call = (call_index(&self.metadata, pallet, call)?, args);
+		let nonce = self.api.get_nonce().map_err(|e| CError::Other(format!("{:?}", e)))?;
+		let xt = self.api.compose_extrinsic_offline(call, nonce);
+
+		self.api
+			.submit_and_watch_extrinsic_until(xt, XtStatus::InBlock)
+			.map_err(|e| CError::Other(format!("Submit extrinsic error: {:?}", e)))
 	}
 }
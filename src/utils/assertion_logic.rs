@@ -0,0 +1,192 @@
+use crate::primitives::vc::{AssertionLogic, CredentialSubject, Op};
+use chrono::NaiveDate;
+use std::{cmp::Ordering, collections::HashMap};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The value bound to a variable (`$has_web2_account`, `$total_amount` ...) of an
+/// `AssertionLogic`. The `dst` of an item is parsed as the type of its `src` variable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+	Bool(bool),
+	Number(f64),
+	/// `YYYY-MM-DD`
+	Date(NaiveDate),
+	String(String),
+}
+
+impl Value {
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			Value::Bool(_) => "bool",
+			Value::Number(_) => "number",
+			Value::Date(_) => "date",
+			Value::String(_) => "string",
+		}
+	}
+
+	/// Parses `dst` as the same type as `self`.
+	fn parse_like(&self, dst: &str) -> Option<Value> {
+		match self {
+			Value::Bool(_) => dst.parse().ok().map(Value::Bool),
+			Value::Number(_) => dst.parse().ok().map(Value::Number),
+			Value::Date(_) => NaiveDate::parse_from_str(dst, DATE_FORMAT).ok().map(Value::Date),
+			Value::String(_) => Some(Value::String(dst.to_string())),
+		}
+	}
+
+	fn compare(&self, other: &Value) -> Option<Ordering> {
+		match (self, other) {
+			(Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+			(Value::Date(a), Value::Date(b)) => a.partial_cmp(b),
+			_ => None,
+		}
+	}
+}
+
+impl From<bool> for Value {
+	fn from(value: bool) -> Self {
+		Value::Bool(value)
+	}
+}
+
+impl From<f64> for Value {
+	fn from(value: f64) -> Self {
+		Value::Number(value)
+	}
+}
+
+impl From<NaiveDate> for Value {
+	fn from(value: NaiveDate) -> Self {
+		Value::Date(value)
+	}
+}
+
+impl From<&str> for Value {
+	fn from(value: &str) -> Self {
+		Value::String(value.to_string())
+	}
+}
+
+impl From<String> for Value {
+	fn from(value: String) -> Self {
+		Value::String(value)
+	}
+}
+
+/// Variable name (with the leading `$`) -> value
+pub type Bindings = HashMap<String, Value>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+	UnknownVariable(String),
+	/// `dst` can't be parsed as the type of the `src` variable.
+	TypeMismatch {
+		src: String,
+		expected: &'static str,
+		dst: String,
+	},
+	/// Only `==` and `!=` are supported for bools and strings.
+	UnsupportedOp {
+		src: String,
+		op: Op,
+	},
+}
+
+/// Evaluates `logic` with `bindings`.
+/// The whole tree is evaluated, so that all the errors are reported and not only the first one.
+pub fn evaluate(logic: &AssertionLogic, bindings: &Bindings) -> Result<bool, Vec<EvalError>> {
+	match logic {
+		AssertionLogic::Item { src, op, dst } =>
+			evaluate_item(src, *op, dst, bindings).map_err(|e| vec![e]),
+		AssertionLogic::And { items } =>
+			evaluate_items(items, bindings, |results| results.iter().all(|result| *result)),
+		AssertionLogic::Or { items } =>
+			evaluate_items(items, bindings, |results| results.iter().any(|result| *result)),
+	}
+}
+
+fn evaluate_items<F>(
+	items: &[Box<AssertionLogic>],
+	bindings: &Bindings,
+	combine: F,
+) -> Result<bool, Vec<EvalError>>
+where
+	F: FnOnce(&[bool]) -> bool,
+{
+	let mut results = vec![];
+	let mut errors = vec![];
+	for item in items {
+		match evaluate(item, bindings) {
+			Ok(result) => results.push(result),
+			Err(e) => errors.extend(e),
+		}
+	}
+
+	if errors.is_empty() {
+		Ok(combine(&results))
+	} else {
+		Err(errors)
+	}
+}
+
+fn evaluate_item(src: &str, op: Op, dst: &str, bindings: &Bindings) -> Result<bool, EvalError> {
+	let value = bindings.get(src).ok_or_else(|| EvalError::UnknownVariable(src.to_string()))?;
+	let expected = value.parse_like(dst).ok_or_else(|| EvalError::TypeMismatch {
+		src: src.to_string(),
+		expected: value.type_name(),
+		dst: dst.to_string(),
+	})?;
+
+	let result = match op {
+		Op::Equal => value == &expected,
+		Op::NotEq => value != &expected,
+		_ => {
+			let ordering = value
+				.compare(&expected)
+				.ok_or_else(|| EvalError::UnsupportedOp { src: src.to_string(), op })?;
+			match op {
+				Op::GreaterThan => ordering == Ordering::Greater,
+				Op::LessThan => ordering == Ordering::Less,
+				Op::GreaterEq => ordering != Ordering::Less,
+				Op::LessEq => ordering != Ordering::Greater,
+				Op::Equal | Op::NotEq => unreachable!(),
+			}
+		},
+	};
+
+	Ok(result)
+}
+
+/// The re-evaluation of one of the `assertions` of a credential subject.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueCheck {
+	pub index: usize,
+	/// The value claimed in `values`, `None` when `values` is shorter than `assertions`.
+	pub claimed: Option<bool>,
+	pub evaluated: Result<bool, Vec<EvalError>>,
+}
+
+impl ValueCheck {
+	pub fn is_consistent(&self) -> bool {
+		match (&self.evaluated, self.claimed) {
+			(Ok(evaluated), Some(claimed)) => *evaluated == claimed,
+			_ => false,
+		}
+	}
+}
+
+/// Re-evaluates each of the subject `assertions` with `bindings` and compares the results with the
+/// claimed `values`.
+pub fn check_values(subject: &CredentialSubject, bindings: &Bindings) -> Vec<ValueCheck> {
+	subject
+		.assertions
+		.iter()
+		.enumerate()
+		.map(|(index, logic)| ValueCheck {
+			index,
+			claimed: subject.values.get(index).copied(),
+			evaluated: evaluate(logic, bindings),
+		})
+		.collect()
+}
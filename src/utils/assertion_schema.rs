@@ -0,0 +1,232 @@
+use crate::primitives::{assertion::Assertion, vc::Credential};
+use jsonschema::{Draft, JSONSchema};
+use std::sync::OnceLock;
+
+/// The JSON schema of the credentials issued for one `Assertion` variant, see
+/// `docs/templates/assertions`.
+/// Parameterised assertions list the credential types and descriptions of each parameter, the
+/// Achainable ones, whose type depends on the Achainable name, only constrain their shape.
+pub struct AssertionSchema {
+	pub name: &'static str,
+	pub content: &'static [u8],
+	compiled: OnceLock<Result<CompiledSchema, String>>,
+}
+
+struct CompiledSchema {
+	schema: JSONSchema,
+	/// The `credentialSubject.type` values allowed by the schema, empty when they aren't listed.
+	credential_types: Vec<String>,
+}
+
+impl AssertionSchema {
+	pub const fn new(name: &'static str, content: &'static [u8]) -> Self {
+		Self { name, content, compiled: OnceLock::new() }
+	}
+
+	/// The `credentialSubject.type` values of the credentials, empty for the Achainable ones.
+	pub fn credential_types(&self) -> &[String] {
+		match self.compiled() {
+			Ok(compiled) => &compiled.credential_types,
+			Err(_) => &[],
+		}
+	}
+
+	/// The schema is compiled on first use and kept, compiling is much slower than validating.
+	fn compiled(&self) -> &Result<CompiledSchema, String> {
+		self.compiled.get_or_init(|| {
+			let schema: serde_json::Value =
+				serde_json::from_slice(self.content).map_err(|e| e.to_string())?;
+			let credential_type = &schema["properties"]["credentialSubject"]["properties"]["type"];
+			let credential_types = match (&credential_type["const"], &credential_type["enum"]) {
+				(serde_json::Value::String(t), _) => vec![t.clone()],
+				(_, serde_json::Value::Array(types)) =>
+					types.iter().filter_map(|t| t.as_str().map(str::to_string)).collect(),
+				_ => vec![],
+			};
+			let schema = JSONSchema::options()
+				.with_draft(Draft::Draft202012)
+				.compile(&schema)
+				.map_err(|e| e.to_string())?;
+
+			Ok(CompiledSchema { schema, credential_types })
+		})
+	}
+}
+
+impl std::fmt::Debug for AssertionSchema {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("AssertionSchema")
+			.field("name", &self.name)
+			.finish_non_exhaustive()
+	}
+}
+
+macro_rules! assertion_schema {
+	($name:literal) => {
+		AssertionSchema::new(
+			$name,
+			include_bytes!(concat!("../../docs/templates/assertions/", $name, ".json")),
+		)
+	};
+}
+
+pub static ASSERTION_SCHEMAS: [AssertionSchema; 23] = [
+	assertion_schema!("a1"),
+	assertion_schema!("a2"),
+	assertion_schema!("a3"),
+	assertion_schema!("a4"),
+	assertion_schema!("a6"),
+	assertion_schema!("a7"),
+	assertion_schema!("a8"),
+	assertion_schema!("a10"),
+	assertion_schema!("a11"),
+	assertion_schema!("a13"),
+	assertion_schema!("a14"),
+	assertion_schema!("a20"),
+	assertion_schema!("achainable"),
+	assertion_schema!("oneblock"),
+	assertion_schema!("generic_discord_role"),
+	assertion_schema!("bnb_domain_holding"),
+	assertion_schema!("bnb_digit_domain_club"),
+	assertion_schema!("vip3_membership_card"),
+	assertion_schema!("weirdo_ghost_gang_holder"),
+	assertion_schema!("lit_staking"),
+	assertion_schema!("evm_amount_holding"),
+	assertion_schema!("brc20_amount_holder"),
+	assertion_schema!("crypto_summary"),
+];
+
+/// The schema every credential is checked against, whatever the assertion.
+pub const COMMON_SCHEMA: &[u8] = include_bytes!("../../docs/templates/vc_schema.json");
+
+static COMMON: AssertionSchema = AssertionSchema::new("common", COMMON_SCHEMA);
+
+pub fn assertion_schema(assertion: &Assertion) -> &'static AssertionSchema {
+	let name = match assertion {
+		Assertion::A1 => "a1",
+		Assertion::A2(..) => "a2",
+		Assertion::A3(..) => "a3",
+		Assertion::A4(..) => "a4",
+		Assertion::A6 => "a6",
+		Assertion::A7(..) => "a7",
+		Assertion::A8(..) => "a8",
+		Assertion::A10(..) => "a10",
+		Assertion::A11(..) => "a11",
+		Assertion::A13(..) => "a13",
+		Assertion::A14 => "a14",
+		Assertion::A20 => "a20",
+		Assertion::Achainable(..) => "achainable",
+		Assertion::Oneblock(..) => "oneblock",
+		Assertion::GenericDiscordRole(..) => "generic_discord_role",
+		Assertion::BnbDomainHolding => "bnb_domain_holding",
+		Assertion::BnbDigitDomainClub(..) => "bnb_digit_domain_club",
+		Assertion::VIP3MembershipCard(..) => "vip3_membership_card",
+		Assertion::WeirdoGhostGangHolder => "weirdo_ghost_gang_holder",
+		Assertion::LITStaking => "lit_staking",
+		Assertion::EVMAmountHolding(..) => "evm_amount_holding",
+		Assertion::BRC20AmountHolder => "brc20_amount_holder",
+		Assertion::CryptoSummary => "crypto_summary",
+	};
+
+	ASSERTION_SCHEMAS
+		.iter()
+		.find(|schema| schema.name == name)
+		.expect("every assertion has a schema")
+}
+
+/// The schemas listing `credential_type`, several assertions may issue the same type, e.g. the
+/// `Token Holding Amount` of `EVMAmountHolding` and `BRC20AmountHolder`.
+pub fn assertion_schemas_by_type(
+	credential_type: &str,
+) -> impl Iterator<Item = &'static AssertionSchema> + '_ {
+	ASSERTION_SCHEMAS
+		.iter()
+		.filter(move |schema| schema.credential_types().iter().any(|t| t == credential_type))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaError {
+	/// Name of the schema that rejected the credential, `common` for `COMMON_SCHEMA`.
+	pub schema: &'static str,
+	/// JSON pointer to the invalid value, e.g. `/credentialSubject/type`
+	pub instance_path: String,
+	pub message: String,
+}
+
+/// Validates `vc` against the common schema and the schema of its assertion, picked from
+/// `credentialSubject.type`. The credential is valid when one of the schemas listing its type
+/// accepts it, credentials of an unlisted type, e.g. the Achainable ones, are rejected: use
+/// `validate_vc_schema_for` with their assertion.
+pub fn validate_vc_schema(vc: &Credential) -> Result<(), Vec<SchemaError>> {
+	let instance = to_instance(vc)?;
+	let mut errors = validate_with(&COMMON, &instance);
+
+	let mut schema_errors = vec![];
+	let mut schemas = assertion_schemas_by_type(&vc.credential_subject.types).peekable();
+	if schemas.peek().is_none() {
+		schema_errors.push(SchemaError {
+			schema: "common",
+			instance_path: "/credentialSubject/type".to_string(),
+			message: format!(
+				"no assertion schema lists the credential type {:?}",
+				vc.credential_subject.types
+			),
+		});
+	}
+	for schema in schemas {
+		let errors = validate_with(schema, &instance);
+		if errors.is_empty() {
+			schema_errors.clear();
+			break
+		}
+		schema_errors.extend(errors);
+	}
+	errors.extend(schema_errors);
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+/// Like `validate_vc_schema`, with the schema of `assertion`, e.g. the one of the `VCContext`.
+pub fn validate_vc_schema_for(
+	vc: &Credential,
+	assertion: &Assertion,
+) -> Result<(), Vec<SchemaError>> {
+	let instance = to_instance(vc)?;
+	let mut errors = validate_with(&COMMON, &instance);
+	errors.extend(validate_with(assertion_schema(assertion), &instance));
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+fn to_instance(vc: &Credential) -> Result<serde_json::Value, Vec<SchemaError>> {
+	serde_json::to_value(vc).map_err(|e| {
+		vec![SchemaError { schema: "common", instance_path: String::new(), message: e.to_string() }]
+	})
+}
+
+fn validate_with(schema: &AssertionSchema, instance: &serde_json::Value) -> Vec<SchemaError> {
+	let error = |instance_path: String, message: String| SchemaError {
+		schema: schema.name,
+		instance_path,
+		message,
+	};
+
+	let compiled = match schema.compiled() {
+		Ok(compiled) => compiled,
+		Err(e) => return vec![error(String::new(), e.clone())],
+	};
+
+	let result = compiled.schema.validate(instance);
+	match result {
+		Ok(()) => vec![],
+		Err(errors) => errors.map(|e| error(e.instance_path.to_string(), e.to_string())).collect(),
+	}
+}
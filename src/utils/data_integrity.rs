@@ -0,0 +1,220 @@
+use crate::{
+	primitives::vc::{Credential, CREDENTIALS_V2_CONTEXT},
+	utils::{
+		jcs,
+		json_ld::{to_rdf, DATA_INTEGRITY_V2_CONTEXT},
+		rdfc,
+	},
+};
+use basex_rs::{BaseX, ALPHABET_BITCOIN};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sp_core::{
+	ed25519::{self, Pair as Ed25519Pair},
+	Pair,
+};
+
+/// W3C Data Integrity (https://www.w3.org/TR/vc-di-eddsa/) proofs, as an alternative to the
+/// `Proof` of `Credential` which signs the `serde_json` output of the struct.
+pub const DATA_INTEGRITY_PROOF_TYPE: &str = "DataIntegrityProof";
+
+/// Multicodec prefix of an Ed25519 public key
+const ED25519_PUB_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cryptosuite {
+	/// RDF Dataset Canonicalization, the document must be fully described by its contexts.
+	EddsaRdfc2022,
+	/// JSON Canonicalization Scheme
+	EddsaJcs2022,
+}
+
+impl Cryptosuite {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Cryptosuite::EddsaRdfc2022 => "eddsa-rdfc-2022",
+			Cryptosuite::EddsaJcs2022 => "eddsa-jcs-2022",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"eddsa-rdfc-2022" => Some(Cryptosuite::EddsaRdfc2022),
+			"eddsa-jcs-2022" => Some(Cryptosuite::EddsaJcs2022),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+	#[serde(rename = "type")]
+	pub proof_type: String,
+	pub cryptosuite: String,
+	/// `xsd:dateTime`, e.g. `2023-10-07T07:23:58Z`
+	pub created: String,
+	/// `did:key` of the signing key
+	pub verification_method: String,
+	pub proof_purpose: String,
+	/// Multibase (base58btc) signature
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub proof_value: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataIntegrityError {
+	Json(String),
+	/// The document is not a JSON object.
+	InvalidDocument,
+	MissingProof,
+	UnsupportedProofType(String),
+	UnsupportedCryptosuite(String),
+	/// The document can't be converted to RDF without dropping part of it, see `json_ld::to_rdf`.
+	UnsafeJsonLd(Vec<String>),
+	Canonicalization(String),
+	VerificationMethodMismatch(String),
+	InvalidProofValue,
+	InvalidSignature,
+}
+
+/// `did:key:z6Mk...#z6Mk...`
+pub fn ed25519_did_key(public: &ed25519::Public) -> String {
+	let key = [&ED25519_PUB_MULTICODEC[..], &public.0[..]].concat();
+	let multibase = format!("z{}", BaseX::with_alphabet(ALPHABET_BITCOIN).to_bs58(&key));
+	format!("did:key:{}#{}", multibase, multibase)
+}
+
+/// The credential as a JSON document secured with a Data Integrity proof instead of its `proof`.
+/// `eddsa-rdfc-2022` only accepts documents fully described by the bundled W3C contexts, which
+/// the Litentry members (`issuanceTimestamp`, `credentialSubject.assertions`...) aren't, use
+/// `eddsa-jcs-2022` for Litentry credentials.
+pub fn sign_vc_data_integrity(
+	vc: &Credential,
+	pair: &Ed25519Pair,
+	cryptosuite: Cryptosuite,
+	created: u64,
+) -> Result<Value, DataIntegrityError> {
+	let mut vc = vc.clone();
+	vc.proof = None;
+	let document =
+		serde_json::to_value(&vc).map_err(|e| DataIntegrityError::Json(e.to_string()))?;
+
+	add_data_integrity_proof(&document, pair, cryptosuite, created)
+}
+
+/// Adds an `assertionMethod` proof to `document`, `created` is a unix timestamp in milliseconds.
+/// The Data Integrity context is appended to the `@context` of the document when missing, since
+/// it defines the proof terms, unless the credentials v2 context (which defines them too) is used.
+pub fn add_data_integrity_proof(
+	document: &Value,
+	pair: &Ed25519Pair,
+	cryptosuite: Cryptosuite,
+	created: u64,
+) -> Result<Value, DataIntegrityError> {
+	let mut document = document.clone();
+	let map = document.as_object_mut().ok_or(DataIntegrityError::InvalidDocument)?;
+	map.remove("proof");
+	let mut contexts = match map.remove("@context") {
+		Some(Value::Array(contexts)) => contexts,
+		Some(context) => vec![context],
+		None => vec![],
+	};
+	if !contexts
+		.iter()
+		.any(|context| context == DATA_INTEGRITY_V2_CONTEXT || context == CREDENTIALS_V2_CONTEXT)
+	{
+		contexts.push(Value::String(DATA_INTEGRITY_V2_CONTEXT.to_string()));
+	}
+	map.insert("@context".to_string(), Value::Array(contexts));
+
+	let created = chrono::NaiveDateTime::from_timestamp_millis(created as i64)
+		.ok_or_else(|| DataIntegrityError::Json(format!("Invalid timestamp {}", created)))?;
+	let mut proof = DataIntegrityProof {
+		proof_type: DATA_INTEGRITY_PROOF_TYPE.to_string(),
+		cryptosuite: cryptosuite.name().to_string(),
+		created: created.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+		verification_method: ed25519_did_key(&pair.public()),
+		proof_purpose: "assertionMethod".to_string(),
+		proof_value: None,
+	};
+
+	let signature = pair.sign(&hash_data(&document, &proof, cryptosuite)?);
+	proof.proof_value =
+		Some(format!("z{}", BaseX::with_alphabet(ALPHABET_BITCOIN).to_bs58(&signature.0)));
+
+	let proof =
+		serde_json::to_value(&proof).map_err(|e| DataIntegrityError::Json(e.to_string()))?;
+	if let Some(map) = document.as_object_mut() {
+		map.insert("proof".to_string(), proof);
+	}
+
+	Ok(document)
+}
+
+/// Verifies the Data Integrity proof of `document` was made by `vc_pubkey`.
+pub fn verify_data_integrity(
+	document: &Value,
+	vc_pubkey: &ed25519::Public,
+) -> Result<(), DataIntegrityError> {
+	let mut document = document.clone();
+	let proof = document
+		.as_object_mut()
+		.ok_or(DataIntegrityError::InvalidDocument)?
+		.remove("proof")
+		.ok_or(DataIntegrityError::MissingProof)?;
+	let mut proof: DataIntegrityProof =
+		serde_json::from_value(proof).map_err(|e| DataIntegrityError::Json(e.to_string()))?;
+
+	if proof.proof_type != DATA_INTEGRITY_PROOF_TYPE {
+		return Err(DataIntegrityError::UnsupportedProofType(proof.proof_type))
+	}
+	let cryptosuite = Cryptosuite::from_name(&proof.cryptosuite)
+		.ok_or_else(|| DataIntegrityError::UnsupportedCryptosuite(proof.cryptosuite.clone()))?;
+	if proof.verification_method != ed25519_did_key(vc_pubkey) {
+		return Err(DataIntegrityError::VerificationMethodMismatch(proof.verification_method))
+	}
+
+	let signature = proof
+		.proof_value
+		.take()
+		.and_then(|value| value.strip_prefix('z').map(String::from))
+		.and_then(|value| BaseX::with_alphabet(ALPHABET_BITCOIN).from_bs58(&value))
+		.and_then(|value| ed25519::Signature::from_slice(&value))
+		.ok_or(DataIntegrityError::InvalidProofValue)?;
+
+	if !Ed25519Pair::verify(&signature, hash_data(&document, &proof, cryptosuite)?, vc_pubkey) {
+		return Err(DataIntegrityError::InvalidSignature)
+	}
+
+	Ok(())
+}
+
+/// `sha256(canonical proof configuration) ++ sha256(canonical document)`
+fn hash_data(
+	document: &Value,
+	proof: &DataIntegrityProof,
+	cryptosuite: Cryptosuite,
+) -> Result<Vec<u8>, DataIntegrityError> {
+	let mut proof_config =
+		serde_json::to_value(proof).map_err(|e| DataIntegrityError::Json(e.to_string()))?;
+	if let (Some(map), Some(context)) = (proof_config.as_object_mut(), document.get("@context")) {
+		map.insert("@context".to_string(), context.clone());
+	}
+
+	let proof_config_hash = Sha256::digest(canonicalize(&proof_config, cryptosuite)?.as_bytes());
+	let document_hash = Sha256::digest(canonicalize(document, cryptosuite)?.as_bytes());
+
+	Ok([&proof_config_hash[..], &document_hash[..]].concat())
+}
+
+fn canonicalize(document: &Value, cryptosuite: Cryptosuite) -> Result<String, DataIntegrityError> {
+	match cryptosuite {
+		Cryptosuite::EddsaJcs2022 => Ok(jcs::canonicalize(document)),
+		Cryptosuite::EddsaRdfc2022 => {
+			let quads = to_rdf(document).map_err(DataIntegrityError::UnsafeJsonLd)?;
+			rdfc::canonicalize(&quads).map_err(DataIntegrityError::Canonicalization)
+		},
+	}
+}
@@ -0,0 +1,111 @@
+use crate::{
+	primitives::{cerror::CError, enclave::Enclave, AccountId, BlockAt, CResult, MrEnclave},
+	utils::hex::hex_encode,
+	Creek, ParachainOp,
+};
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Bumped whenever the JSON layout of `EnclaveRegistrySnapshot` changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Where the verifier looks up the enclaves which may have issued a credential.
+pub trait EnclaveLookup {
+	fn enclaves(&self) -> CResult<Vec<Enclave<AccountId, String>>>;
+
+	fn enclaves_by_mrenclave(
+		&self,
+		mrenclave: &MrEnclave,
+	) -> CResult<Vec<Enclave<AccountId, String>>> {
+		let enclaves = self
+			.enclaves()?
+			.into_iter()
+			.filter(|enclave| &enclave.mr_enclave == mrenclave)
+			.collect();
+
+		Ok(enclaves)
+	}
+}
+
+/// Live lookup, only the currently registered enclaves are known.
+impl<T: ParachainOp> EnclaveLookup for T {
+	fn enclaves(&self) -> CResult<Vec<Enclave<AccountId, String>>> {
+		self.enclave_registry()
+	}
+}
+
+/// Offline copy of `Teerex::EnclaveRegistry`.
+/// Snapshots can be merged, so that credentials issued by enclaves which have been removed since
+/// can still be verified.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnclaveRegistrySnapshot {
+	pub version: u32,
+	/// 0x prefixed hex of the block hashes the enclaves were read at
+	pub block_hashes: Vec<String>,
+	/// 0x prefixed hex of the SCALE encoded enclaves
+	pub enclaves: Vec<String>,
+}
+
+impl EnclaveRegistrySnapshot {
+	pub fn take(creek: &Creek, at: BlockAt) -> CResult<Self> {
+		let block_hash = creek.parachain_client.block_hash(at)?;
+		let enclaves = creek.enclave_registry_at(BlockAt::Hash(block_hash))?;
+
+		Ok(Self::new(vec![hex_encode(block_hash.as_bytes())], &enclaves))
+	}
+
+	pub fn new(block_hashes: Vec<String>, enclaves: &[Enclave<AccountId, String>]) -> Self {
+		Self {
+			version: SNAPSHOT_VERSION,
+			block_hashes,
+			enclaves: enclaves.iter().map(|enclave| hex_encode(&enclave.encode())).collect(),
+		}
+	}
+
+	/// Adds the enclaves of `other` which are not part of this snapshot yet.
+	pub fn merge(&mut self, other: &Self) {
+		for block_hash in &other.block_hashes {
+			if !self.block_hashes.contains(block_hash) {
+				self.block_hashes.push(block_hash.clone());
+			}
+		}
+		for enclave in &other.enclaves {
+			if !self.enclaves.contains(enclave) {
+				self.enclaves.push(enclave.clone());
+			}
+		}
+	}
+
+	pub fn save(&self, path: impl AsRef<Path>) -> CResult<()> {
+		let json = serde_json::to_string_pretty(self).map_err(CError::DecodeJsonError)?;
+		fs::write(path, json).map_err(|e| CError::Other(format!("{:?}", e)))
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> CResult<Self> {
+		let json = fs::read(path).map_err(|e| CError::Other(format!("{:?}", e)))?;
+		let snapshot: Self = serde_json::from_slice(&json).map_err(CError::DecodeJsonError)?;
+		if snapshot.version != SNAPSHOT_VERSION {
+			return Err(CError::Other(format!(
+				"Unsupported EnclaveRegistry snapshot version: {}",
+				snapshot.version
+			)))
+		}
+
+		Ok(snapshot)
+	}
+}
+
+impl EnclaveLookup for EnclaveRegistrySnapshot {
+	fn enclaves(&self) -> CResult<Vec<Enclave<AccountId, String>>> {
+		self.enclaves
+			.iter()
+			.map(|enclave| {
+				let encoded =
+					hex::decode(enclave.trim_start_matches("0x")).map_err(CError::FromHexError)?;
+				Enclave::decode(&mut encoded.as_slice()).map_err(CError::CodecError)
+			})
+			.collect()
+	}
+}
@@ -71,7 +71,7 @@ pub fn hex_encode(data: &[u8]) -> String {
 /// Helper method for decoding hex.
 pub fn decode_hex<T: AsRef<[u8]>>(message: T) -> Result<Vec<u8>> {
 	let mut message = message.as_ref();
-	if message[..2] == [b'0', b'x'] {
+	if message.starts_with(b"0x") {
 		message = &message[2..]
 	}
 	let decoded_message = hex::decode(message).map_err(Error::Hex)?;
@@ -0,0 +1,130 @@
+//! JSON Canonicalization Scheme (RFC 8785).
+
+use serde_json::Value;
+
+/// The canonical JSON of `value`: no whitespace, object members sorted by the UTF-16 code units of
+/// their names, and numbers serialised like ECMAScript `Number.prototype.toString`.
+pub fn canonicalize(value: &Value) -> String {
+	let mut out = String::new();
+	write_value(&mut out, value);
+	out
+}
+
+fn write_value(out: &mut String, value: &Value) {
+	match value {
+		Value::Null => out.push_str("null"),
+		Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+		Value::Number(n) => out.push_str(&number_to_string(n.as_f64().unwrap_or_default())),
+		Value::String(s) => write_string(out, s),
+		Value::Array(items) => {
+			out.push('[');
+			for (i, item) in items.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				write_value(out, item);
+			}
+			out.push(']');
+		},
+		Value::Object(map) => {
+			let mut entries: Vec<_> = map.iter().collect();
+			entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+			out.push('{');
+			for (i, (key, value)) in entries.into_iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				write_string(out, key);
+				out.push(':');
+				write_value(out, value);
+			}
+			out.push('}');
+		},
+	}
+}
+
+fn write_string(out: &mut String, s: &str) {
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\u{08}' => out.push_str("\\b"),
+			'\u{0c}' => out.push_str("\\f"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
+
+/// ECMAScript `Number.prototype.toString` of a finite double.
+pub fn number_to_string(value: f64) -> String {
+	if value == 0.0 || !value.is_finite() {
+		return "0".to_string()
+	}
+
+	// Rust's `{:e}` is the shortest representation that round trips, like ECMAScript.
+	let formatted = format!("{:e}", value.abs());
+	let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+	let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+	let k = digits.len() as i32;
+	let n = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+	let abs = if k <= n && n <= 21 {
+		format!("{}{}", digits, "0".repeat((n - k) as usize))
+	} else if 0 < n && n <= 21 {
+		format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+	} else if -6 < n && n <= 0 {
+		format!("0.{}{}", "0".repeat(-n as usize), digits)
+	} else {
+		let sign = if n - 1 < 0 { '-' } else { '+' };
+		let fraction = if k == 1 { String::new() } else { format!(".{}", &digits[1..]) };
+		format!("{}{}e{}{}", &digits[..1], fraction, sign, (n - 1).abs())
+	};
+
+	if value < 0.0 {
+		format!("-{}", abs)
+	} else {
+		abs
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	#[test]
+	fn number_to_string_works() {
+		assert_eq!(number_to_string(0.0), "0");
+		assert_eq!(number_to_string(-0.0), "0");
+		assert_eq!(number_to_string(1696663438000.0), "1696663438000");
+		assert_eq!(number_to_string(4.5), "4.5");
+		assert_eq!(number_to_string(0.002), "0.002");
+		assert_eq!(number_to_string(0.000001), "0.000001");
+		assert_eq!(number_to_string(0.0000001), "1e-7");
+		assert_eq!(number_to_string(1e21), "1e+21");
+		assert_eq!(number_to_string(-1.5e30), "-1.5e+30");
+		assert_eq!(number_to_string(333333333.3333333), "333333333.3333333");
+		assert_eq!(number_to_string(9007199254740992.0), "9007199254740992");
+	}
+
+	#[test]
+	fn canonicalize_works() {
+		let value = json!({
+			"numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+			"string": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+			"literals": [null, true, false]
+		});
+
+		assert_eq!(
+			canonicalize(&value),
+			r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+		);
+	}
+}
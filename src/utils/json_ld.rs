@@ -0,0 +1,568 @@
+//! A restricted JSON-LD to RDF conversion, enough to canonicalize credentials with `rdfc`.
+//! Only the bundled contexts can be referenced, nothing is fetched. The conversion runs in "safe
+//! mode": anything a JSON-LD processor would silently drop (undefined terms, relative IRIs...) is
+//! reported as an error instead, so that a signature can't leave part of a document unsecured.
+
+use crate::{
+	primitives::vc::CREDENTIALS_V1_CONTEXT,
+	utils::{
+		jcs,
+		rdfc::{
+			Quad, Term, RDF_FIRST, RDF_JSON, RDF_NIL, RDF_REST, RDF_TYPE, XSD_BOOLEAN, XSD_DOUBLE,
+			XSD_INTEGER, XSD_STRING,
+		},
+	},
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub const ED25519_2020_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+pub const DATA_INTEGRITY_V2_CONTEXT: &str = "https://w3id.org/security/data-integrity/v2";
+
+/// Contexts which can be referenced by URL.
+pub fn bundled_context(url: &str) -> Option<&'static [u8]> {
+	match url {
+		CREDENTIALS_V1_CONTEXT => Some(include_bytes!("../../docs/contexts/credentials-v1.jsonld")),
+		ED25519_2020_CONTEXT => Some(include_bytes!("../../docs/contexts/ed25519-2020-v1.jsonld")),
+		DATA_INTEGRITY_V2_CONTEXT =>
+			Some(include_bytes!("../../docs/contexts/data-integrity-v2.jsonld")),
+		_ => None,
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+struct TermDefinition {
+	/// Absolute IRI, blank node or keyword, `None` when the term is explicitly mapped to null.
+	id: Option<String>,
+	type_mapping: Option<String>,
+	container: Vec<String>,
+	context: Option<Value>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Context {
+	terms: HashMap<String, TermDefinition>,
+	vocab: Option<String>,
+	/// The context before a type scoped context was applied, type scoped contexts don't propagate
+	/// to nested nodes.
+	previous: Option<Box<Context>>,
+}
+
+impl Context {
+	fn process(&self, local: &Value, propagate: bool) -> Result<Context, String> {
+		let mut result = self.clone();
+		if !propagate && result.previous.is_none() {
+			result.previous = Some(Box::new(self.clone()));
+		}
+		result.merge(local, 0)?;
+		Ok(result)
+	}
+
+	fn merge(&mut self, local: &Value, depth: usize) -> Result<(), String> {
+		if depth > 8 {
+			return Err("Context nesting is too deep".to_string())
+		}
+
+		match local {
+			Value::Array(contexts) =>
+				for context in contexts {
+					self.merge(context, depth + 1)?;
+				},
+			Value::Null => {
+				self.terms.clear();
+				self.vocab = None;
+			},
+			Value::String(url) => {
+				let document = bundled_context(url)
+					.ok_or_else(|| format!("Context {} is not bundled", url))?;
+				let document: Value =
+					serde_json::from_slice(document).map_err(|e| format!("{:?}", e))?;
+				self.merge(&document["@context"], depth + 1)?;
+			},
+			Value::Object(definitions) => self.define_terms(definitions)?,
+			_ => return Err(format!("Invalid context {}", local)),
+		}
+
+		Ok(())
+	}
+
+	fn define_terms(&mut self, definitions: &Map<String, Value>) -> Result<(), String> {
+		if let Some(vocab) = definitions.get("@vocab") {
+			self.vocab = match vocab {
+				Value::String(vocab) => Some(self.expand_iri(vocab, true)),
+				_ => None,
+			};
+		}
+
+		let mut defined = HashMap::new();
+		for term in definitions.keys() {
+			self.define_term(definitions, term, &mut defined)?;
+		}
+
+		Ok(())
+	}
+
+	fn define_term(
+		&mut self,
+		definitions: &Map<String, Value>,
+		term: &str,
+		defined: &mut HashMap<String, bool>,
+	) -> Result<(), String> {
+		match defined.get(term) {
+			Some(true) => return Ok(()),
+			Some(false) => return Err(format!("Cyclic IRI mapping for {}", term)),
+			None => {},
+		}
+		if matches!(term, "@version" | "@protected" | "@vocab" | "@propagate") {
+			return Ok(())
+		}
+		if term.starts_with('@') {
+			return Err(format!("Unsupported context keyword {}", term))
+		}
+		defined.insert(term.to_string(), false);
+
+		let value = &definitions[term];
+		let definition = match value {
+			Value::Null => TermDefinition::default(),
+			Value::String(id) => {
+				self.define_prefix_of(definitions, id, defined)?;
+				TermDefinition { id: Some(self.expand_iri(id, true)), ..Default::default() }
+			},
+			Value::Object(map) => {
+				for key in map.keys() {
+					if !matches!(
+						key.as_str(),
+						"@id" | "@type" | "@container" | "@context" | "@protected"
+					) {
+						return Err(format!("Unsupported term definition {} in {}", key, term))
+					}
+				}
+
+				let id = match map.get("@id") {
+					Some(Value::String(id)) => {
+						self.define_prefix_of(definitions, id, defined)?;
+						Some(self.expand_iri(id, true))
+					},
+					Some(Value::Null) => None,
+					Some(_) => return Err(format!("Invalid @id of {}", term)),
+					None => {
+						self.define_prefix_of(definitions, term, defined)?;
+						Some(self.expand_iri(term, true))
+					},
+				};
+				let type_mapping = match map.get("@type") {
+					Some(Value::String(type_mapping)) => {
+						self.define_prefix_of(definitions, type_mapping, defined)?;
+						Some(self.expand_iri(type_mapping, true))
+					},
+					Some(_) => return Err(format!("Invalid @type of {}", term)),
+					None => None,
+				};
+				let container = match map.get("@container") {
+					Some(Value::String(container)) => vec![container.clone()],
+					Some(Value::Array(containers)) =>
+						containers.iter().filter_map(|c| c.as_str().map(String::from)).collect(),
+					Some(_) => return Err(format!("Invalid @container of {}", term)),
+					None => vec![],
+				};
+
+				TermDefinition {
+					id,
+					type_mapping,
+					container,
+					context: map.get("@context").cloned(),
+				}
+			},
+			_ => return Err(format!("Invalid term definition of {}", term)),
+		};
+
+		if let Some(id) = &definition.id {
+			if !id.starts_with('@') && !id.starts_with("_:") && !is_absolute_iri(id) {
+				return Err(format!("{} doesn't map to an absolute IRI", term))
+			}
+		}
+
+		self.terms.insert(term.to_string(), definition);
+		defined.insert(term.to_string(), true);
+		Ok(())
+	}
+
+	/// Defines the prefix of the compact IRI `value` first when it's part of `definitions`.
+	fn define_prefix_of(
+		&mut self,
+		definitions: &Map<String, Value>,
+		value: &str,
+		defined: &mut HashMap<String, bool>,
+	) -> Result<(), String> {
+		if let Some((prefix, suffix)) = value.split_once(':') {
+			if !suffix.starts_with("//") && definitions.contains_key(prefix) {
+				self.define_term(definitions, prefix, defined)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	fn definition(&self, term: &str) -> Option<&TermDefinition> {
+		self.terms.get(term)
+	}
+
+	/// IRI expansion, `vocab` is set for properties and types. There is no base IRI, so relative
+	/// IRIs are left as is.
+	fn expand_iri(&self, value: &str, vocab: bool) -> String {
+		if value.starts_with('@') {
+			return value.to_string()
+		}
+		if vocab {
+			if let Some(definition) = self.definition(value) {
+				return definition.id.clone().unwrap_or_default()
+			}
+		}
+		if let Some((prefix, suffix)) = value.split_once(':') {
+			if prefix == "_" || suffix.starts_with("//") {
+				return value.to_string()
+			}
+			if let Some(TermDefinition { id: Some(id), .. }) = self.definition(prefix) {
+				return format!("{}{}", id, suffix)
+			}
+			if is_absolute_iri(value) {
+				return value.to_string()
+			}
+		}
+		if vocab {
+			if let Some(vocab) = &self.vocab {
+				return format!("{}{}", vocab, value)
+			}
+		}
+		value.to_string()
+	}
+}
+
+fn is_absolute_iri(value: &str) -> bool {
+	match value.split_once(':') {
+		Some((scheme, _)) =>
+			scheme.chars().next().map_or(false, |c| c.is_ascii_alphabetic()) &&
+				scheme
+					.chars()
+					.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')),
+		None => false,
+	}
+}
+
+struct ToRdf {
+	quads: Vec<Quad>,
+	blank_nodes: HashMap<String, String>,
+	counter: usize,
+	/// What a JSON-LD processor would have dropped
+	issues: Vec<String>,
+}
+
+impl ToRdf {
+	fn blank_node(&mut self, label: Option<&str>) -> Term {
+		if let Some(existing) = label.and_then(|label| self.blank_nodes.get(label)) {
+			return Term::BlankNode(existing.clone())
+		}
+
+		let issued = format!("b{}", self.counter);
+		self.counter += 1;
+		if let Some(label) = label {
+			self.blank_nodes.insert(label.to_string(), issued.clone());
+		}
+		Term::BlankNode(issued)
+	}
+
+	fn reference(&mut self, iri: String, what: &str) -> Option<Term> {
+		if let Some(label) = iri.strip_prefix("_:") {
+			Some(self.blank_node(Some(label)))
+		} else if is_absolute_iri(&iri) {
+			Some(Term::Iri(iri))
+		} else {
+			self.issues.push(format!("{} `{}` is not an absolute IRI", what, iri));
+			None
+		}
+	}
+
+	fn emit(&mut self, subject: &Term, predicate: &str, object: Term) {
+		self.quads.push(Quad {
+			subject: subject.clone(),
+			predicate: Term::Iri(predicate.to_string()),
+			object,
+			graph: None,
+		});
+	}
+
+	/// Converts a JSON object, the node (or literal / list) it stands for is returned.
+	fn map(
+		&mut self,
+		active: &Context,
+		map: &Map<String, Value>,
+		property_scoped: Option<&Value>,
+	) -> Result<Option<Term>, String> {
+		let mut ctx = active.clone();
+		let is_value = map.keys().any(|key| ctx.expand_iri(key, true) == "@value");
+		let is_reference =
+			map.len() == 1 && ctx.expand_iri(map.keys().next().unwrap(), true) == "@id";
+		if !is_value && !is_reference {
+			if let Some(previous) = ctx.previous.take() {
+				ctx = *previous;
+			}
+		}
+		if let Some(scoped) = property_scoped {
+			ctx = ctx.process(scoped, true)?;
+		}
+		if let Some(local) = map.get("@context") {
+			ctx = ctx.process(local, true)?;
+		}
+
+		let mut types = vec![];
+		for (key, value) in map {
+			if ctx.expand_iri(key, true) == "@type" {
+				types.extend(as_array(value).iter().filter_map(|t| t.as_str()).map(String::from));
+			}
+		}
+		types.sort();
+		let mut type_ctx = ctx.clone();
+		for t in &types {
+			if let Some(TermDefinition { context: Some(scoped), .. }) = ctx.definition(t) {
+				type_ctx = type_ctx.process(scoped, false)?;
+			}
+		}
+
+		let mut keys = vec![];
+		for key in map.keys() {
+			let expanded = type_ctx.expand_iri(key, true);
+			match expanded.as_str() {
+				"@context" | "@type" => {},
+				"@value" => return self.value_object(&type_ctx, map).map(Some),
+				"@list" => {
+					let items = self.values(&type_ctx, &map[key], None)?;
+					return Ok(Some(self.list(items)))
+				},
+				_ => keys.push((key, expanded)),
+			}
+		}
+
+		let subject = match keys.iter().find(|(_, expanded)| expanded == "@id") {
+			Some((key, _)) => match &map[key.as_str()] {
+				Value::String(id) => {
+					let iri = type_ctx.expand_iri(id, false);
+					match self.reference(iri, "@id") {
+						Some(subject) => subject,
+						None => self.blank_node(None),
+					}
+				},
+				_ => return Err("@id must be a string".to_string()),
+			},
+			None => self.blank_node(None),
+		};
+
+		for t in &types {
+			let iri = type_ctx.expand_iri(t, true);
+			match self.reference(iri, "@type") {
+				Some(Term::Iri(iri)) => self.emit(&subject, RDF_TYPE, Term::Iri(iri)),
+				Some(_) => self.issues.push(format!("Blank node type `{}`", t)),
+				None => {},
+			}
+		}
+
+		for (key, expanded) in keys {
+			if expanded == "@id" {
+				continue
+			}
+			if expanded.starts_with('@') {
+				self.issues.push(format!("Unsupported keyword `{}`", expanded));
+				continue
+			}
+			if expanded.is_empty() || !is_absolute_iri(&expanded) {
+				self.issues.push(format!("Undefined term `{}`", key));
+				continue
+			}
+
+			let definition = type_ctx.definition(key).cloned();
+			let container = definition.as_ref().map(|d| d.container.clone()).unwrap_or_default();
+			if container.iter().any(|c| c != "@set" && c != "@list") {
+				self.issues.push(format!("Unsupported container of `{}`", key));
+				continue
+			}
+
+			let objects = self.values(&type_ctx, &map[key.as_str()], definition.as_ref())?;
+			if container.iter().any(|c| c == "@list") {
+				let list = self.list(objects);
+				self.emit(&subject, &expanded, list);
+			} else {
+				for object in objects {
+					self.emit(&subject, &expanded, object);
+				}
+			}
+		}
+
+		Ok(Some(subject))
+	}
+
+	/// Arrays are flattened, `null`s are dropped. A `@json` value is a single literal, arrays
+	/// included.
+	fn values(
+		&mut self,
+		ctx: &Context,
+		value: &Value,
+		definition: Option<&TermDefinition>,
+	) -> Result<Vec<Term>, String> {
+		if definition.and_then(|d| d.type_mapping.as_deref()) == Some("@json") {
+			return Ok(vec![Term::literal(jcs::canonicalize(value), RDF_JSON)])
+		}
+
+		let mut terms = vec![];
+		for item in as_array(value) {
+			match item {
+				Value::Array(_) => terms.extend(self.values(ctx, item, definition)?),
+				item =>
+					if let Some(term) = self.value(ctx, item, definition)? {
+						terms.push(term);
+					},
+			}
+		}
+
+		Ok(terms)
+	}
+
+	fn value(
+		&mut self,
+		ctx: &Context,
+		value: &Value,
+		definition: Option<&TermDefinition>,
+	) -> Result<Option<Term>, String> {
+		let type_mapping = definition.and_then(|d| d.type_mapping.as_deref());
+		let scoped = definition.and_then(|d| d.context.as_ref());
+
+		let term = match value {
+			Value::Null => None,
+			Value::Object(map) => self.map(ctx, map, scoped)?,
+			Value::String(s) => match type_mapping {
+				Some("@id") | Some("@vocab") => {
+					let ctx = match scoped {
+						Some(scoped) => ctx.process(scoped, true)?,
+						None => ctx.clone(),
+					};
+					let iri = ctx.expand_iri(s, type_mapping == Some("@vocab"));
+					self.reference(iri, "Value")
+				},
+				Some(datatype) if !datatype.starts_with('@') => Some(Term::literal(s, datatype)),
+				_ => Some(Term::literal(s, XSD_STRING)),
+			},
+			Value::Bool(b) =>
+				Some(Term::literal(b.to_string(), datatype_or(type_mapping, XSD_BOOLEAN))),
+			Value::Number(n) => Some(number_literal(n.as_f64().unwrap_or_default(), type_mapping)),
+			Value::Array(_) => unreachable!("arrays are flattened"),
+		};
+
+		Ok(term)
+	}
+
+	fn value_object(&mut self, ctx: &Context, map: &Map<String, Value>) -> Result<Term, String> {
+		let mut value = &Value::Null;
+		let mut datatype = None;
+		let mut language = None;
+		for (key, item) in map {
+			match ctx.expand_iri(key, true).as_str() {
+				"@value" => value = item,
+				"@type" => datatype = item.as_str().map(|t| ctx.expand_iri(t, true)),
+				"@language" => language = item.as_str().map(|l| l.to_lowercase()),
+				_ => return Err(format!("Invalid value object member {}", key)),
+			}
+		}
+
+		if datatype.as_deref() == Some("@json") {
+			return Ok(Term::literal(jcs::canonicalize(value), RDF_JSON))
+		}
+		if let Some(datatype) = &datatype {
+			if !is_absolute_iri(datatype) {
+				self.issues.push(format!("Datatype `{}` is not an absolute IRI", datatype));
+			}
+		}
+
+		let term = match value {
+			Value::String(s) => match language {
+				Some(language) => Term::Literal {
+					value: s.clone(),
+					datatype: String::new(),
+					language: Some(language),
+				},
+				None => Term::literal(s, datatype.as_deref().unwrap_or(XSD_STRING)),
+			},
+			Value::Bool(b) =>
+				Term::literal(b.to_string(), datatype_or(datatype.as_deref(), XSD_BOOLEAN)),
+			Value::Number(n) => number_literal(n.as_f64().unwrap_or_default(), datatype.as_deref()),
+			_ => return Err("Invalid @value".to_string()),
+		};
+
+		Ok(term)
+	}
+
+	fn list(&mut self, items: Vec<Term>) -> Term {
+		let nodes: Vec<Term> = items.iter().map(|_| self.blank_node(None)).collect();
+		for (i, item) in items.into_iter().enumerate() {
+			self.emit(&nodes[i], RDF_FIRST, item);
+			let rest = nodes.get(i + 1).cloned().unwrap_or_else(|| Term::Iri(RDF_NIL.to_string()));
+			self.emit(&nodes[i], RDF_REST, rest);
+		}
+
+		nodes.into_iter().next().unwrap_or_else(|| Term::Iri(RDF_NIL.to_string()))
+	}
+}
+
+fn as_array(value: &Value) -> Vec<&Value> {
+	match value {
+		Value::Array(items) => items.iter().collect(),
+		value => vec![value],
+	}
+}
+
+fn datatype_or<'a>(type_mapping: Option<&'a str>, default: &'a str) -> &'a str {
+	match type_mapping {
+		Some(datatype) if !datatype.starts_with('@') => datatype,
+		_ => default,
+	}
+}
+
+fn number_literal(n: f64, type_mapping: Option<&str>) -> Term {
+	let datatype = datatype_or(type_mapping, XSD_INTEGER);
+	if n.fract() == 0.0 && n.abs() < 1e21 && datatype == XSD_INTEGER {
+		return Term::literal(format!("{}", n as i128), XSD_INTEGER)
+	}
+
+	let datatype = if datatype == XSD_INTEGER { XSD_DOUBLE } else { datatype };
+	Term::literal(canonical_double(n), datatype)
+}
+
+/// `1.5E0` form of `xsd:double`
+fn canonical_double(n: f64) -> String {
+	let formatted = format!("{:.15e}", n);
+	let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+	let mantissa = mantissa.trim_end_matches('0');
+	let mantissa =
+		if mantissa.ends_with('.') { format!("{}0", mantissa) } else { mantissa.to_string() };
+	format!("{}E{}", mantissa, exponent)
+}
+
+/// The RDF dataset of a JSON-LD document, everything that can't be converted without loss is
+/// returned as an error.
+pub fn to_rdf(document: &Value) -> Result<Vec<Quad>, Vec<String>> {
+	let mut to_rdf =
+		ToRdf { quads: vec![], blank_nodes: HashMap::new(), counter: 0, issues: vec![] };
+
+	for item in as_array(document) {
+		match item {
+			Value::Object(map) =>
+				if let Err(e) = to_rdf.map(&Context::default(), map, None) {
+					to_rdf.issues.push(e);
+				},
+			_ => to_rdf.issues.push("Top level values must be objects".to_string()),
+		}
+	}
+
+	if to_rdf.issues.is_empty() {
+		Ok(to_rdf.quads)
+	} else {
+		Err(to_rdf.issues)
+	}
+}
@@ -1,7 +1,23 @@
 pub mod address;
+pub mod assertion_logic;
+pub mod assertion_schema;
 pub mod crypto;
+pub mod data_integrity;
+pub mod enclave_registry;
 pub mod hex;
 pub mod identity;
+pub mod jcs;
+pub mod json_ld;
 pub mod macros;
+pub mod presentation;
 pub mod public_api;
+pub mod raw_json;
+pub mod rdfc;
+pub mod sd_jwt;
+pub mod storage;
 pub mod vc;
+pub mod vc_builder;
+pub mod vc_jwt;
+pub mod vc_registry;
+pub mod vc_registry_snapshot;
+pub mod verifier;
@@ -0,0 +1,195 @@
+use crate::{
+	primitives::presentation::{
+		Presentation, PRESENTATION_CONTEXT, PRESENTATION_PROOF_TYPE, PRESENTATION_TYPE,
+	},
+	utils::{
+		raw_json::{array_elements, member},
+		vc::{verify_vc_raw_with, verify_vc_subject, verify_vc_with, SubjectError, VerifyContext},
+	},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PresentationError {
+	/// The presentation JSON can't be parsed.
+	InvalidJson(String),
+	InvalidContext,
+	InvalidType,
+	MissingProof,
+	UnsupportedProofType(String),
+	/// `holder` is not a `did:litentry:` DID.
+	InvalidHolder(String),
+	/// The proof is not signed by the holder.
+	VerificationMethodMismatch(String),
+	InvalidSignature,
+	ChallengeMismatch {
+		expected: String,
+		presented: String,
+	},
+	DomainMismatch {
+		expected: String,
+		presented: String,
+	},
+	NoCredential,
+	/// The credential at `index` is not issued to the holder.
+	CredentialNotHeld {
+		index: usize,
+		errors: Vec<SubjectError>,
+	},
+	/// The credential at `index` doesn't pass `verify_vc_with`.
+	InvalidCredential {
+		index: usize,
+		reason: String,
+	},
+}
+
+/// Verifies a presentation for the relying party which issued `challenge` for `domain`:
+/// * the presentation is signed by its holder, over the `challenge` and `domain`,
+/// * each credential is issued to the holder and is valid for `ctx`.
+/// All the checks are run and the failed ones are returned.
+/// The credentials are verified as `Credential` serialises them, presented JSON has to be verified
+/// with `verify_presentation_raw`.
+pub fn verify_presentation(
+	ctx: &VerifyContext,
+	vp: &Presentation,
+	challenge: &str,
+	domain: &str,
+) -> Result<(), Vec<PresentationError>> {
+	let mut errors = verify_presentation_holder(vp, challenge, domain).err().unwrap_or_default();
+
+	for (index, vc) in vp.verifiable_credential.iter().enumerate() {
+		if let Err(e) = verify_vc_with(ctx, vc).verified() {
+			errors.push(PresentationError::InvalidCredential { index, reason: e.to_string() });
+		}
+	}
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+/// `verify_presentation` of the presentation JSON, each credential is verified against its JSON
+/// as presented, see `presented_credentials`.
+pub fn verify_presentation_raw(
+	ctx: &VerifyContext,
+	json: &[u8],
+	challenge: &str,
+	domain: &str,
+) -> Result<(), Vec<PresentationError>> {
+	let invalid_json = |e: String| vec![PresentationError::InvalidJson(e)];
+	let vp: Presentation =
+		serde_json::from_slice(json).map_err(|e| invalid_json(format!("{:?}", e)))?;
+	let credentials = presented_credentials(json).map_err(invalid_json)?;
+
+	let mut errors = verify_presentation_holder(&vp, challenge, domain).err().unwrap_or_default();
+
+	for (index, vc) in credentials.into_iter().enumerate() {
+		let verified = verify_vc_raw_with(ctx, vc)
+			.and_then(|report| report.verified().map_err(|e| e.to_string()));
+		if let Err(reason) = verified {
+			errors.push(PresentationError::InvalidCredential { index, reason });
+		}
+	}
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+/// The JSON of each `verifiableCredential` of the presentation JSON, as the holder presented it.
+pub fn presented_credentials(json: &[u8]) -> Result<Vec<&[u8]>, String> {
+	match member(json, "verifiableCredential")? {
+		Some(credentials) => array_elements(credentials),
+		None => Ok(vec![]),
+	}
+}
+
+/// The checks of `verify_presentation` which don't depend on the issuer of the credentials: the
+/// signature of the holder and that each credential is issued to them. The credentials are left to
+/// the caller, e.g. when they're issued by different enclaves and each needs its own
+/// `VerifyContext`.
+pub fn verify_presentation_holder(
+	vp: &Presentation,
+	challenge: &str,
+	domain: &str,
+) -> Result<(), Vec<PresentationError>> {
+	let mut errors = vec![];
+
+	if vp.context.first().map(String::as_str) != Some(PRESENTATION_CONTEXT) {
+		errors.push(PresentationError::InvalidContext);
+	}
+	if !vp.types.iter().any(|t| t == PRESENTATION_TYPE) {
+		errors.push(PresentationError::InvalidType);
+	}
+
+	let holder = vp.holder_identity();
+	if holder.is_none() {
+		errors.push(PresentationError::InvalidHolder(vp.holder.clone()));
+	}
+
+	match &vp.proof {
+		Some(proof) => {
+			if proof.proof_type != PRESENTATION_PROOF_TYPE {
+				errors.push(PresentationError::UnsupportedProofType(proof.proof_type.clone()));
+			}
+			if proof.verification_method != vp.holder {
+				errors.push(PresentationError::VerificationMethodMismatch(
+					proof.verification_method.clone(),
+				));
+			}
+			if proof.challenge != challenge {
+				errors.push(PresentationError::ChallengeMismatch {
+					expected: challenge.to_string(),
+					presented: proof.challenge.clone(),
+				});
+			}
+			if proof.domain != domain {
+				errors.push(PresentationError::DomainMismatch {
+					expected: domain.to_string(),
+					presented: proof.domain.clone(),
+				});
+			}
+
+			let verified_signature = match (&holder, vp.signature(), vp.signing_payload()) {
+				(Some(holder), Some(signature), Ok(payload)) => signature.verify(&payload, holder),
+				_ => false,
+			};
+			if !verified_signature {
+				errors.push(PresentationError::InvalidSignature);
+			}
+		},
+		None => errors.push(PresentationError::MissingProof),
+	}
+
+	if vp.verifiable_credential.is_empty() {
+		errors.push(PresentationError::NoCredential);
+	}
+
+	for (index, vc) in vp.verifiable_credential.iter().enumerate() {
+		if let Some(holder) = &holder {
+			let subject_errors: Vec<_> = verify_vc_subject(vc, Some(holder))
+				.err()
+				.unwrap_or_default()
+				.into_iter()
+				.filter(|e| {
+					matches!(
+						e,
+						SubjectError::InvalidSubjectId(_) | SubjectError::HolderMismatch { .. }
+					)
+				})
+				.collect();
+			if !subject_errors.is_empty() {
+				errors.push(PresentationError::CredentialNotHeld { index, errors: subject_errors });
+			}
+		}
+	}
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
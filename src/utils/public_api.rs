@@ -17,12 +17,11 @@ pub fn mrenclave_to_bs58(mrenclave: &MrEnclave) -> String {
 	BaseX::with_alphabet(ALPHABET_BITCOIN).to_bs58(mrenclave)
 }
 
+/// `None` unless `mrenclave_in_base58` decodes to exactly 32 bytes.
 pub fn mrenclave_from_bs58(mrenclave_in_base58: String) -> Option<MrEnclave> {
-	BaseX::with_alphabet(ALPHABET_BITCOIN).from_bs58(&mrenclave_in_base58).map(|m| {
-		let mut bytes = [0u8; 32];
-		bytes[..32].clone_from_slice(&m);
-		bytes
-	})
+	BaseX::with_alphabet(ALPHABET_BITCOIN)
+		.from_bs58(&mrenclave_in_base58)
+		.and_then(|m| m.try_into().ok())
 }
 
 pub fn remove_whitespace(s: &str) -> String {
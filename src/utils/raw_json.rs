@@ -0,0 +1,296 @@
+//! Edits of JSON documents which keep all the other bytes as they are, for signatures and hashes
+//! computed over the exact JSON text.
+
+/// Byte offsets of a member of an object.
+struct Member {
+	key_start: usize,
+	key_end: usize,
+	value_start: usize,
+	value_end: usize,
+}
+
+/// The document without a member, and the raw value of the member.
+type RemovedMember<'a> = (Vec<u8>, &'a [u8]);
+
+/// Removes the member `name` of the top level object of `json` like a serializer skipping it
+/// would have: together with its separator and the whitespace before it. Returns the remaining
+/// document and the raw value of the member, or `None` when the object has no such member.
+pub fn remove_member<'a>(json: &'a [u8], name: &str) -> Result<Option<RemovedMember<'a>>, String> {
+	let members = scan_object(json)?;
+	let index = match find_member(json, &members, name)? {
+		Some(index) => index,
+		None => return Ok(None),
+	};
+
+	let member = &members[index];
+	let (start, end) = if index > 0 {
+		// `,"name":value` after the previous value
+		(members[index - 1].value_end, member.value_end)
+	} else if let Some(next) = members.get(1) {
+		// `"name":value,` up to the next key
+		(member.key_start, next.key_start)
+	} else {
+		(member.key_start, member.value_end)
+	};
+
+	let mut remaining = json[..start].to_vec();
+	remaining.extend_from_slice(&json[end..]);
+
+	Ok(Some((remaining, &json[member.value_start..member.value_end])))
+}
+
+/// The raw value of the member `name` of the top level object of `json`.
+pub fn member<'a>(json: &'a [u8], name: &str) -> Result<Option<&'a [u8]>, String> {
+	let members = scan_object(json)?;
+	let index = find_member(json, &members, name)?;
+
+	Ok(index.map(|index| &json[members[index].value_start..members[index].value_end]))
+}
+
+/// The raw values of the elements of the top level array of `json`.
+pub fn array_elements(json: &[u8]) -> Result<Vec<&[u8]>, String> {
+	let mut scanner = Scanner { json, pos: 0 };
+	let mut elements = vec![];
+
+	scanner.skip_whitespace();
+	scanner.expect(b'[')?;
+	scanner.skip_whitespace();
+	if scanner.peek() == Some(b']') {
+		scanner.pos += 1;
+	} else {
+		loop {
+			scanner.skip_whitespace();
+			let start = scanner.pos;
+			scanner.value()?;
+			elements.push(&json[start..scanner.pos]);
+
+			scanner.skip_whitespace();
+			match scanner.next() {
+				Some(b',') => {},
+				Some(b']') => break,
+				_ => return Err(format!("Expected , or ] at {}", scanner.pos)),
+			}
+		}
+	}
+
+	scanner.skip_whitespace();
+	if scanner.pos != json.len() {
+		return Err(format!("Trailing characters at {}", scanner.pos))
+	}
+
+	Ok(elements)
+}
+
+/// The index of the member `name` in `members`, which can only appear once.
+fn find_member(json: &[u8], members: &[Member], name: &str) -> Result<Option<usize>, String> {
+	let mut index = None;
+	for (i, member) in members.iter().enumerate() {
+		let key = &json[member.key_start..member.key_end];
+		let key: String = serde_json::from_slice(key).map_err(|e| format!("{:?}", e))?;
+		if key == name {
+			if index.is_some() {
+				return Err(format!("Duplicate member {}", name))
+			}
+			index = Some(i);
+		}
+	}
+
+	Ok(index)
+}
+
+fn scan_object(json: &[u8]) -> Result<Vec<Member>, String> {
+	let mut scanner = Scanner { json, pos: 0 };
+	let mut members = vec![];
+
+	scanner.skip_whitespace();
+	scanner.expect(b'{')?;
+	scanner.skip_whitespace();
+	if scanner.peek() == Some(b'}') {
+		scanner.pos += 1;
+	} else {
+		loop {
+			scanner.skip_whitespace();
+			let key_start = scanner.pos;
+			scanner.string()?;
+			let key_end = scanner.pos;
+			scanner.skip_whitespace();
+			scanner.expect(b':')?;
+			scanner.skip_whitespace();
+			let value_start = scanner.pos;
+			scanner.value()?;
+			members.push(Member { key_start, key_end, value_start, value_end: scanner.pos });
+
+			scanner.skip_whitespace();
+			match scanner.next() {
+				Some(b',') => {},
+				Some(b'}') => break,
+				_ => return Err(format!("Expected , or }} at {}", scanner.pos)),
+			}
+		}
+	}
+
+	scanner.skip_whitespace();
+	if scanner.pos != json.len() {
+		return Err(format!("Trailing characters at {}", scanner.pos))
+	}
+
+	Ok(members)
+}
+
+/// Only finds where the values end, `serde_json` decodes them.
+struct Scanner<'a> {
+	json: &'a [u8],
+	pos: usize,
+}
+
+impl Scanner<'_> {
+	fn peek(&self) -> Option<u8> {
+		self.json.get(self.pos).copied()
+	}
+
+	fn next(&mut self) -> Option<u8> {
+		let c = self.peek()?;
+		self.pos += 1;
+		Some(c)
+	}
+
+	fn expect(&mut self, expected: u8) -> Result<(), String> {
+		match self.next() {
+			Some(c) if c == expected => Ok(()),
+			_ => Err(format!("Expected {} at {}", expected as char, self.pos)),
+		}
+	}
+
+	fn skip_whitespace(&mut self) {
+		while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+			self.pos += 1;
+		}
+	}
+
+	fn string(&mut self) -> Result<(), String> {
+		self.expect(b'"')?;
+		loop {
+			match self.next() {
+				Some(b'"') => return Ok(()),
+				Some(b'\\') => {
+					self.next();
+				},
+				Some(_) => {},
+				None => return Err("Unterminated string".to_string()),
+			}
+		}
+	}
+
+	fn value(&mut self) -> Result<(), String> {
+		match self.peek() {
+			Some(b'"') => self.string(),
+			Some(open @ (b'{' | b'[')) => {
+				let close = if open == b'{' { b'}' } else { b']' };
+				self.pos += 1;
+				self.skip_whitespace();
+				if self.peek() == Some(close) {
+					self.pos += 1;
+					return Ok(())
+				}
+				loop {
+					self.skip_whitespace();
+					if open == b'{' {
+						self.string()?;
+						self.skip_whitespace();
+						self.expect(b':')?;
+						self.skip_whitespace();
+					}
+					self.value()?;
+					self.skip_whitespace();
+					match self.next() {
+						Some(b',') => {},
+						Some(c) if c == close => return Ok(()),
+						_ => return Err(format!("Expected , or {} at {}", close as char, self.pos)),
+					}
+				}
+			},
+			Some(_) => {
+				let start = self.pos;
+				while matches!(
+					self.peek(),
+					Some(b'-' | b'+' | b'.' | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z')
+				) {
+					self.pos += 1;
+				}
+				if self.pos == start {
+					return Err(format!("Unexpected character at {}", self.pos))
+				}
+				Ok(())
+			},
+			None => Err("Unexpected end of JSON".to_string()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn remove(json: &str, name: &str) -> Option<(String, String)> {
+		remove_member(json.as_bytes(), name).unwrap().map(|(remaining, value)| {
+			(String::from_utf8(remaining).unwrap(), String::from_utf8(value.to_vec()).unwrap())
+		})
+	}
+
+	#[test]
+	fn remove_member_works() {
+		let json = r#"{"id":"0x1","proof":{"proofValue":"ab","x":[1,{"}":"\""}]},"extra":null}"#;
+		assert_eq!(
+			remove(json, "proof"),
+			Some((
+				r#"{"id":"0x1","extra":null}"#.to_string(),
+				r#"{"proofValue":"ab","x":[1,{"}":"\""}]}"#.to_string()
+			))
+		);
+		assert_eq!(
+			remove(json, "id"),
+			Some((
+				r#"{"proof":{"proofValue":"ab","x":[1,{"}":"\""}]},"extra":null}"#.to_string(),
+				r#""0x1""#.to_string()
+			))
+		);
+		assert_eq!(
+			remove(r#"{ "proof" : 1 }"#, "proof"),
+			Some(("{  }".to_string(), "1".to_string()))
+		);
+		assert_eq!(
+			remove("{\n  \"a\": -1.5e3,\n  \"proof\": true\n}", "proof"),
+			Some(("{\n  \"a\": -1.5e3\n}".to_string(), "true".to_string()))
+		);
+		assert_eq!(remove(r#"{"nested":{"proof":1}}"#, "proof"), None);
+		assert_eq!(remove(r#"{"proof":1}"#, "proof"), Some(("{}".to_string(), "1".to_string())));
+	}
+
+	#[test]
+	fn remove_member_rejects_invalid_json() {
+		assert!(remove_member(br#"{"proof":1,"proof":2}"#, "proof").is_err());
+		assert!(remove_member(br#"{"proof":1"#, "proof").is_err());
+		assert!(remove_member(br#"{"proof":1} x"#, "proof").is_err());
+		assert!(remove_member(br#"[1]"#, "proof").is_err());
+	}
+
+	#[test]
+	fn member_works() {
+		let json = br#"{"a":[1,{"b":"]"}] , "b" : { "c":null }}"#;
+		assert_eq!(member(json, "a").unwrap(), Some(&br#"[1,{"b":"]"}]"#[..]));
+		assert_eq!(member(json, "b").unwrap(), Some(&br#"{ "c":null }"#[..]));
+		assert_eq!(member(json, "c").unwrap(), None);
+		assert!(member(br#"{"a":1,"a":2}"#, "a").is_err());
+	}
+
+	#[test]
+	fn array_elements_works() {
+		let json = b"[ {\"a\":[1,2]},\n\"x,]\" ,-1.5e3,null ]";
+		let elements = array_elements(json).unwrap();
+		assert_eq!(elements, vec![&b"{\"a\":[1,2]}"[..], b"\"x,]\"", b"-1.5e3", b"null"]);
+		assert_eq!(array_elements(b" [ ] ").unwrap(), Vec::<&[u8]>::new());
+		assert!(array_elements(b"[1,]").is_err());
+		assert!(array_elements(b"[1] 2").is_err());
+		assert!(array_elements(b"{}").is_err());
+	}
+}
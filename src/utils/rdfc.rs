@@ -0,0 +1,568 @@
+//! RDF Dataset Canonicalization (RDFC-1.0, formerly URDNA2015).
+
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+pub const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+pub const RDF_FIRST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#first";
+pub const RDF_REST: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#rest";
+pub const RDF_NIL: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#nil";
+pub const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+pub const XSD_STRING: &str = "http://www.w3.org/2001/XMLSchema#string";
+pub const XSD_BOOLEAN: &str = "http://www.w3.org/2001/XMLSchema#boolean";
+pub const XSD_INTEGER: &str = "http://www.w3.org/2001/XMLSchema#integer";
+pub const XSD_DOUBLE: &str = "http://www.w3.org/2001/XMLSchema#double";
+
+/// Bounds the work spent on the N-degree hashes, which is exponential for crafted datasets.
+pub const MAX_N_DEGREE_CALLS: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Term {
+	Iri(String),
+	/// Label without the `_:` prefix
+	BlankNode(String),
+	Literal {
+		value: String,
+		datatype: String,
+		language: Option<String>,
+	},
+}
+
+impl Term {
+	pub fn literal(value: impl Into<String>, datatype: &str) -> Self {
+		Term::Literal { value: value.into(), datatype: datatype.to_string(), language: None }
+	}
+
+	fn blank_node_label(&self) -> Option<&str> {
+		match self {
+			Term::BlankNode(label) => Some(label),
+			_ => None,
+		}
+	}
+
+	fn write(&self, out: &mut String) {
+		match self {
+			Term::Iri(iri) => {
+				out.push('<');
+				out.push_str(iri);
+				out.push('>');
+			},
+			Term::BlankNode(label) => {
+				out.push_str("_:");
+				out.push_str(label);
+			},
+			Term::Literal { value, datatype, language } => {
+				out.push('"');
+				escape_literal(out, value);
+				out.push('"');
+				if let Some(language) = language {
+					out.push('@');
+					out.push_str(language);
+				} else if datatype != XSD_STRING {
+					out.push_str("^^<");
+					out.push_str(datatype);
+					out.push('>');
+				}
+			},
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Quad {
+	pub subject: Term,
+	pub predicate: Term,
+	pub object: Term,
+	/// `None` for the default graph
+	pub graph: Option<Term>,
+}
+
+impl Quad {
+	/// Canonical N-Quads line, with the trailing newline.
+	pub fn to_nquad(&self) -> String {
+		self.to_nquad_with(&|term: &Term| term.clone())
+	}
+
+	fn to_nquad_with(&self, map: &dyn Fn(&Term) -> Term) -> String {
+		let mut out = String::new();
+		map(&self.subject).write(&mut out);
+		out.push(' ');
+		map(&self.predicate).write(&mut out);
+		out.push(' ');
+		map(&self.object).write(&mut out);
+		if let Some(graph) = &self.graph {
+			out.push(' ');
+			map(graph).write(&mut out);
+		}
+		out.push_str(" .\n");
+		out
+	}
+
+	fn blank_nodes(&self) -> impl Iterator<Item = &str> {
+		[Some(&self.subject), Some(&self.object), self.graph.as_ref()]
+			.into_iter()
+			.flatten()
+			.filter_map(Term::blank_node_label)
+	}
+}
+
+fn escape_literal(out: &mut String, value: &str) {
+	for c in value.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\u{08}' => out.push_str("\\b"),
+			'\t' => out.push_str("\\t"),
+			'\n' => out.push_str("\\n"),
+			'\u{0c}' => out.push_str("\\f"),
+			'\r' => out.push_str("\\r"),
+			c if (c as u32) < 0x20 || c == '\u{7f}' =>
+				out.push_str(&format!("\\u{:04X}", c as u32)),
+			c => out.push(c),
+		}
+	}
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+	hex::encode(Sha256::digest(data))
+}
+
+#[derive(Clone, Debug)]
+struct IdentifierIssuer {
+	prefix: &'static str,
+	counter: usize,
+	/// Existing label -> issued label, in issuance order
+	issued: Vec<(String, String)>,
+}
+
+impl IdentifierIssuer {
+	fn new(prefix: &'static str) -> Self {
+		Self { prefix, counter: 0, issued: vec![] }
+	}
+
+	fn get(&self, existing: &str) -> Option<&str> {
+		self.issued
+			.iter()
+			.find(|(label, _)| label == existing)
+			.map(|(_, issued)| issued.as_str())
+	}
+
+	fn issue(&mut self, existing: &str) -> String {
+		if let Some(issued) = self.get(existing) {
+			return issued.to_string()
+		}
+
+		let issued = format!("{}{}", self.prefix, self.counter);
+		self.counter += 1;
+		self.issued.push((existing.to_string(), issued.clone()));
+		issued
+	}
+}
+
+struct Canonicalizer<'a> {
+	quads: &'a [Quad],
+	/// Blank node label -> indexes of the quads it appears in
+	blank_node_quads: HashMap<&'a str, Vec<usize>>,
+	canonical_issuer: IdentifierIssuer,
+	first_degree_hashes: HashMap<&'a str, String>,
+	n_degree_calls: usize,
+}
+
+/// The canonical N-Quads of `quads`: blank nodes are relabelled `c14n0`, `c14n1`... and the lines
+/// are sorted.
+pub fn canonicalize(quads: &[Quad]) -> Result<String, String> {
+	// A dataset is a set, duplicates would change the hashes.
+	let mut unique = quads.to_vec();
+	unique.sort();
+	unique.dedup();
+	let quads = unique.as_slice();
+
+	let mut canonicalizer = Canonicalizer {
+		quads,
+		blank_node_quads: HashMap::new(),
+		canonical_issuer: IdentifierIssuer::new("c14n"),
+		first_degree_hashes: HashMap::new(),
+		n_degree_calls: 0,
+	};
+	for (index, quad) in quads.iter().enumerate() {
+		for label in quad.blank_nodes() {
+			let indexes = canonicalizer.blank_node_quads.entry(label).or_default();
+			if indexes.last() != Some(&index) {
+				indexes.push(index);
+			}
+		}
+	}
+
+	canonicalizer.issue_canonical_labels()?;
+
+	let issuer = &canonicalizer.canonical_issuer;
+	let relabel = |term: &Term| match term {
+		Term::BlankNode(label) => Term::BlankNode(issuer.get(label).unwrap_or(label).to_string()),
+		term => term.clone(),
+	};
+	let mut lines: Vec<String> = quads.iter().map(|quad| quad.to_nquad_with(&relabel)).collect();
+	lines.sort();
+
+	Ok(lines.concat())
+}
+
+impl<'a> Canonicalizer<'a> {
+	fn issue_canonical_labels(&mut self) -> Result<(), String> {
+		let labels: Vec<&'a str> = self.blank_node_quads.keys().copied().collect();
+		let mut hash_to_labels: BTreeMap<String, Vec<&'a str>> = BTreeMap::new();
+		for label in labels {
+			let hash = self.hash_first_degree(label);
+			hash_to_labels.entry(hash).or_default().push(label);
+		}
+
+		let mut shared = vec![];
+		for labels in hash_to_labels.into_values() {
+			match labels[..] {
+				[label] => {
+					self.canonical_issuer.issue(label);
+				},
+				_ => shared.push(labels),
+			}
+		}
+
+		for labels in shared {
+			let mut hash_paths = vec![];
+			for label in labels {
+				if self.canonical_issuer.get(label).is_some() {
+					continue
+				}
+				let mut issuer = IdentifierIssuer::new("b");
+				issuer.issue(label);
+				hash_paths.push(self.hash_n_degree_quads(label, issuer)?);
+			}
+
+			hash_paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+			for (_, issuer) in hash_paths {
+				for (label, _) in issuer.issued {
+					self.canonical_issuer.issue(&label);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn hash_first_degree(&mut self, label: &'a str) -> String {
+		if let Some(hash) = self.first_degree_hashes.get(label) {
+			return hash.clone()
+		}
+
+		let replace = |term: &Term| match term {
+			Term::BlankNode(other) if other == label => Term::BlankNode("a".to_string()),
+			Term::BlankNode(_) => Term::BlankNode("z".to_string()),
+			term => term.clone(),
+		};
+		let mut lines: Vec<String> = self.blank_node_quads[label]
+			.iter()
+			.map(|index| self.quads[*index].to_nquad_with(&replace))
+			.collect();
+		lines.sort();
+
+		let hash = sha256_hex(lines.concat().as_bytes());
+		self.first_degree_hashes.insert(label, hash.clone());
+		hash
+	}
+
+	fn hash_related_blank_node(
+		&mut self,
+		related: &'a str,
+		quad: &Quad,
+		issuer: &IdentifierIssuer,
+		position: char,
+	) -> String {
+		let identifier = match self.canonical_issuer.get(related).or_else(|| issuer.get(related)) {
+			Some(identifier) => format!("_:{}", identifier),
+			None => self.hash_first_degree(related),
+		};
+
+		let mut input = position.to_string();
+		if position != 'g' {
+			input.push('<');
+			if let Term::Iri(predicate) = &quad.predicate {
+				input.push_str(predicate);
+			}
+			input.push('>');
+		}
+		input.push_str(&identifier);
+
+		sha256_hex(input.as_bytes())
+	}
+
+	fn hash_n_degree_quads(
+		&mut self,
+		label: &'a str,
+		mut issuer: IdentifierIssuer,
+	) -> Result<(String, IdentifierIssuer), String> {
+		self.n_degree_calls += 1;
+		if self.n_degree_calls > MAX_N_DEGREE_CALLS {
+			return Err("Too many blank node permutations".to_string())
+		}
+
+		let mut hash_to_related: BTreeMap<String, Vec<&'a str>> = BTreeMap::new();
+		let quads = self.quads;
+		for index in self.blank_node_quads[label].clone() {
+			let quad = &quads[index];
+			let components = [(&quad.subject, 's'), (&quad.object, 'o')]
+				.into_iter()
+				.chain(quad.graph.as_ref().map(|graph| (graph, 'g')));
+			for (term, position) in components {
+				match term {
+					Term::BlankNode(related) if related != label => {
+						let hash = self.hash_related_blank_node(related, quad, &issuer, position);
+						hash_to_related.entry(hash).or_default().push(related);
+					},
+					_ => {},
+				}
+			}
+		}
+
+		let mut data_to_hash = String::new();
+		for (related_hash, related) in hash_to_related {
+			data_to_hash.push_str(&related_hash);
+
+			let mut chosen: Option<(String, IdentifierIssuer)> = None;
+			'permutations: for permutation in permutations(&related) {
+				let mut issuer_copy = issuer.clone();
+				let mut path = String::new();
+				let mut recursion_list = vec![];
+
+				for related in permutation.iter().copied() {
+					match self.canonical_issuer.get(related) {
+						Some(canonical) => path.push_str(&format!("_:{}", canonical)),
+						None => {
+							if issuer_copy.get(related).is_none() {
+								recursion_list.push(related);
+							}
+							path.push_str(&format!("_:{}", issuer_copy.issue(related)));
+						},
+					}
+					if is_worse_path(&path, &chosen) {
+						continue 'permutations
+					}
+				}
+
+				for related in recursion_list {
+					let (hash, result_issuer) =
+						self.hash_n_degree_quads(related, issuer_copy.clone())?;
+					path.push_str(&format!("_:{}", issuer_copy.issue(related)));
+					path.push_str(&format!("<{}>", hash));
+					issuer_copy = result_issuer;
+					if is_worse_path(&path, &chosen) {
+						continue 'permutations
+					}
+				}
+
+				if chosen.as_ref().map_or(true, |(chosen_path, _)| path < *chosen_path) {
+					chosen = Some((path, issuer_copy));
+				}
+			}
+
+			if let Some((chosen_path, chosen_issuer)) = chosen {
+				data_to_hash.push_str(&chosen_path);
+				issuer = chosen_issuer;
+			}
+		}
+
+		Ok((sha256_hex(data_to_hash.as_bytes()), issuer))
+	}
+}
+
+fn is_worse_path(path: &str, chosen: &Option<(String, IdentifierIssuer)>) -> bool {
+	match chosen {
+		Some((chosen_path, _)) => path.len() >= chosen_path.len() && path > chosen_path.as_str(),
+		None => false,
+	}
+}
+
+fn permutations<T: Copy>(items: &[T]) -> Vec<Vec<T>> {
+	if items.len() <= 1 {
+		return vec![items.to_vec()]
+	}
+
+	let mut result = vec![];
+	for i in 0..items.len() {
+		let mut rest = items.to_vec();
+		let item = rest.remove(i);
+		for mut permutation in permutations(&rest) {
+			permutation.insert(0, item);
+			result.push(permutation);
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(nquads: &str) -> Vec<Quad> {
+		let term = |s: &str| match s.strip_prefix("_:") {
+			Some(label) => Term::BlankNode(label.to_string()),
+			None => Term::Iri(s.trim_matches(|c| c == '<' || c == '>').to_string()),
+		};
+		nquads
+			.lines()
+			.map(|line| {
+				let parts: Vec<&str> = line.split_whitespace().collect();
+				Quad {
+					subject: term(parts[0]),
+					predicate: term(parts[1]),
+					object: term(parts[2]),
+					graph: if parts.len() == 5 { Some(term(parts[3])) } else { None },
+				}
+			})
+			.collect()
+	}
+
+	#[test]
+	fn canonicalize_unique_hashes_works() {
+		let dataset = parse(
+			"<http://example.com/#p> <http://example.com/#q> _:e0 .
+			<http://example.com/#p> <http://example.com/#r> _:e1 .
+			_:e0 <http://example.com/#s> <http://example.com/#u> .
+			_:e1 <http://example.com/#t> <http://example.com/#u> .",
+		);
+
+		assert_eq!(
+			canonicalize(&dataset).unwrap(),
+			"<http://example.com/#p> <http://example.com/#q> _:c14n0 .\n\
+			 <http://example.com/#p> <http://example.com/#r> _:c14n1 .\n\
+			 _:c14n0 <http://example.com/#s> <http://example.com/#u> .\n\
+			 _:c14n1 <http://example.com/#t> <http://example.com/#u> .\n"
+		);
+	}
+
+	#[test]
+	fn canonicalize_shared_hashes_works() {
+		let dataset = parse(
+			"<http://example.com/#p> <http://example.com/#q> _:e0 .
+			<http://example.com/#p> <http://example.com/#q> _:e1 .
+			_:e0 <http://example.com/#p> _:e2 .
+			_:e1 <http://example.com/#p> _:e3 .
+			_:e2 <http://example.com/#r> _:e3 .",
+		);
+
+		assert_eq!(
+			canonicalize(&dataset).unwrap(),
+			"<http://example.com/#p> <http://example.com/#q> _:c14n2 .\n\
+			 <http://example.com/#p> <http://example.com/#q> _:c14n3 .\n\
+			 _:c14n0 <http://example.com/#r> _:c14n1 .\n\
+			 _:c14n2 <http://example.com/#p> _:c14n1 .\n\
+			 _:c14n3 <http://example.com/#p> _:c14n0 .\n"
+		);
+	}
+
+	// Blank node relabelling cases in the style of the RDFC-1.0 test suite: symmetric datasets
+	// where every blank node has the same first degree hash.
+
+	#[test]
+	fn canonicalize_double_circle_works() {
+		let dataset = parse(
+			"_:e0 <http://example.com/#p> _:e1 .
+			_:e1 <http://example.com/#p> _:e0 .
+			_:e2 <http://example.com/#p> _:e3 .
+			_:e3 <http://example.com/#p> _:e2 .",
+		);
+
+		assert_eq!(
+			canonicalize(&dataset).unwrap(),
+			"_:c14n0 <http://example.com/#p> _:c14n1 .\n\
+			 _:c14n1 <http://example.com/#p> _:c14n0 .\n\
+			 _:c14n2 <http://example.com/#p> _:c14n3 .\n\
+			 _:c14n3 <http://example.com/#p> _:c14n2 .\n"
+		);
+	}
+
+	#[test]
+	fn canonicalize_point_at_circle_works() {
+		let expected = "<http://example.com/#x> <http://example.com/#q> _:c14n0 .\n\
+			_:c14n0 <http://example.com/#p> _:c14n1 .\n\
+			_:c14n1 <http://example.com/#p> _:c14n2 .\n\
+			_:c14n2 <http://example.com/#p> _:c14n0 .\n";
+
+		// The labels follow the circle from the pointed node, whatever the input labels.
+		for pointed in ["e0", "e1", "e2"] {
+			let dataset = parse(&format!(
+				"<http://example.com/#x> <http://example.com/#q> _:{} .
+				_:e0 <http://example.com/#p> _:e1 .
+				_:e1 <http://example.com/#p> _:e2 .
+				_:e2 <http://example.com/#p> _:e0 .",
+				pointed
+			));
+			assert_eq!(canonicalize(&dataset).unwrap(), expected);
+		}
+	}
+
+	#[test]
+	fn canonicalize_clique_works() {
+		let dataset = parse(
+			"_:e0 <http://example.com/#p> _:e1 .
+			_:e0 <http://example.com/#p> _:e2 .
+			_:e1 <http://example.com/#p> _:e0 .
+			_:e1 <http://example.com/#p> _:e2 .
+			_:e2 <http://example.com/#p> _:e0 .
+			_:e2 <http://example.com/#p> _:e1 .",
+		);
+
+		assert_eq!(
+			canonicalize(&dataset).unwrap(),
+			"_:c14n0 <http://example.com/#p> _:c14n1 .\n\
+			 _:c14n0 <http://example.com/#p> _:c14n2 .\n\
+			 _:c14n1 <http://example.com/#p> _:c14n0 .\n\
+			 _:c14n1 <http://example.com/#p> _:c14n2 .\n\
+			 _:c14n2 <http://example.com/#p> _:c14n0 .\n\
+			 _:c14n2 <http://example.com/#p> _:c14n1 .\n"
+		);
+	}
+
+	#[test]
+	fn canonicalize_circle_with_chord_works() {
+		let expected = "_:c14n0 <http://example.com/#p> _:c14n3 .\n\
+			_:c14n1 <http://example.com/#p> _:c14n2 .\n\
+			_:c14n1 <http://example.com/#q> _:c14n0 .\n\
+			_:c14n2 <http://example.com/#p> _:c14n0 .\n\
+			_:c14n3 <http://example.com/#p> _:c14n1 .\n";
+
+		let dataset = parse(
+			"_:e0 <http://example.com/#p> _:e1 .
+			_:e1 <http://example.com/#p> _:e2 .
+			_:e2 <http://example.com/#p> _:e3 .
+			_:e3 <http://example.com/#p> _:e0 .
+			_:e1 <http://example.com/#q> _:e3 .",
+		);
+		assert_eq!(canonicalize(&dataset).unwrap(), expected);
+
+		// The same dataset, labelled the other way around the circle.
+		let dataset = parse(
+			"_:e3 <http://example.com/#p> _:e2 .
+			_:e2 <http://example.com/#p> _:e1 .
+			_:e1 <http://example.com/#p> _:e0 .
+			_:e0 <http://example.com/#p> _:e3 .
+			_:e2 <http://example.com/#q> _:e0 .",
+		);
+		assert_eq!(canonicalize(&dataset).unwrap(), expected);
+	}
+
+	#[test]
+	fn canonicalize_blank_node_graphs_works() {
+		let dataset = parse(
+			"_:e0 <http://example.com/#p> <http://example.com/#x> _:g0 .
+			_:e1 <http://example.com/#p> <http://example.com/#x> _:g1 .
+			_:g0 <http://example.com/#q> _:e0 .
+			<http://example.com/#x> <http://example.com/#q> _:g1 .",
+		);
+
+		assert_eq!(
+			canonicalize(&dataset).unwrap(),
+			"<http://example.com/#x> <http://example.com/#q> _:c14n1 .\n\
+			 _:c14n0 <http://example.com/#q> _:c14n2 .\n\
+			 _:c14n2 <http://example.com/#p> <http://example.com/#x> _:c14n0 .\n\
+			 _:c14n3 <http://example.com/#p> <http://example.com/#x> _:c14n1 .\n"
+		);
+	}
+}
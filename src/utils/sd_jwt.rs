@@ -0,0 +1,347 @@
+use crate::{
+	primitives::{
+		identity::Identity, keypair::KeyPair, signature::LitentryMultiSignature, vc::Credential,
+	},
+	utils::{
+		vc::{subject_identity, TimePolicy},
+		vc_jwt::{
+			decode_base64, decode_segment, encode_base64, encode_segment, sign_jws, split,
+			verify_jws, JwtError, JwtHeader, VcJwtClaims, JWT_ALGORITHM,
+		},
+	},
+};
+use codec::{Decode, Encode};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sp_core::{
+	ed25519::{self, Pair as Ed25519Pair},
+	Pair,
+};
+use std::{collections::HashMap, fmt};
+
+/// SD-JWT (https://datatracker.ietf.org/doc/draft-ietf-oauth-selective-disclosure-jwt/) of a
+/// VC-JWT: each entry of the subject `assertions` and `values` is replaced by the digest of its
+/// disclosure, and the holder picks which ones to reveal.
+pub const SD_ALG: &str = "sha-256";
+pub const KB_JWT_TYPE: &str = "kb+jwt";
+/// Not a registered JWS algorithm, but a Litentry-only profile: the signature segment is the SCALE
+/// encoded `LitentryMultiSignature` of the signing input, verified like a presentation signature.
+/// It's only used for the holder keys no registered algorithm covers (sr25519), an ed25519 holder
+/// signs its key binding JWT with `EdDSA`. Generic SD-JWT verifiers won't accept it.
+pub const KB_JWT_ALGORITHM: &str = "LitentryMultiSignature";
+
+const DISCLOSED_FIELDS: [&str; 2] = ["assertions", "values"];
+
+/// `<jwt>~<disclosure>~...~<key binding jwt>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdJwt {
+	pub jwt: String,
+	pub disclosures: Vec<String>,
+	pub key_binding: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindingClaims {
+	/// The challenge of the verifier
+	pub nonce: String,
+	/// The verifier the presentation is intended for
+	pub aud: String,
+	/// Unix time in seconds
+	pub iat: u64,
+	/// Digest of the presented `<jwt>~<disclosure>~...~`
+	pub sd_hash: String,
+}
+
+/// The credential with only the disclosed `assertions` and `values`, and their original indexes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisclosedCredential {
+	pub credential: Credential,
+	pub indexes: Vec<usize>,
+}
+
+impl SdJwt {
+	/// Issues `vc` with all its assertions disclosable, the issuer hands out all the disclosures.
+	pub fn issue(vc: &Credential, pair: &Ed25519Pair) -> Result<Self, JwtError> {
+		let claims = VcJwtClaims::from_credential(vc);
+		let mut payload =
+			serde_json::to_value(&claims).map_err(|e| JwtError::Malformed(format!("{:?}", e)))?;
+
+		let mut disclosures = vec![];
+		for field in DISCLOSED_FIELDS {
+			let entries = &mut payload["vc"]["credentialSubject"][field];
+			let items = entries.as_array().cloned().unwrap_or_default();
+			let mut digests = vec![];
+			for item in items {
+				let salt = encode_base64(rand::thread_rng().gen::<[u8; 16]>());
+				let disclosure = encode_segment(&json!([salt, item]))?;
+				digests.push(json!({ "...": digest(&disclosure) }));
+				disclosures.push(disclosure);
+			}
+			*entries = Value::Array(digests);
+		}
+		payload["_sd_alg"] = json!(SD_ALG);
+
+		Ok(Self { jwt: sign_jws(&payload, pair)?, disclosures, key_binding: None })
+	}
+
+	pub fn parse(sd_jwt: &str) -> Result<Self, JwtError> {
+		let mut parts: Vec<&str> = sd_jwt.split('~').collect();
+		if parts.len() < 2 {
+			return Err(JwtError::Malformed("Missing ~ separator".to_string()))
+		}
+
+		let key_binding = parts.pop().filter(|kb| !kb.is_empty()).map(String::from);
+		let jwt = parts.remove(0).to_string();
+		let disclosures = parts.into_iter().map(String::from).collect();
+
+		Ok(Self { jwt, disclosures, key_binding })
+	}
+
+	/// Keeps the disclosures of the assertions at `indexes` (and their values) only.
+	pub fn select(&self, indexes: &[usize]) -> Result<Self, JwtError> {
+		let (_, payload) = split(&self.jwt)?;
+		let payload: Value = decode_segment(payload)?;
+
+		let mut positions = HashMap::new();
+		for field in DISCLOSED_FIELDS {
+			let entries = payload["vc"]["credentialSubject"][field].as_array().cloned();
+			for (index, entry) in entries.unwrap_or_default().iter().enumerate() {
+				if let Some(digest) = entry["..."].as_str() {
+					positions.insert(digest.to_string(), index);
+				}
+			}
+		}
+
+		let disclosures = self
+			.disclosures
+			.iter()
+			.filter(|disclosure| {
+				positions
+					.get(&digest(disclosure))
+					.map_or(false, |index| indexes.contains(index))
+			})
+			.cloned()
+			.collect();
+
+		Ok(Self { jwt: self.jwt.clone(), disclosures, key_binding: None })
+	}
+
+	/// Reveals the assertions at `indexes` to the verifier which issued `nonce` for `aud`, the
+	/// presentation is bound to the subject account with a key binding JWT signed by `holder`,
+	/// see `KB_JWT_ALGORITHM`.
+	pub fn present(
+		&self,
+		indexes: &[usize],
+		holder: &KeyPair,
+		nonce: &str,
+		aud: &str,
+		iat: u64,
+	) -> Result<Self, JwtError> {
+		let mut presentation = self.select(indexes)?;
+
+		let alg = match holder {
+			KeyPair::Ed25519(_) => JWT_ALGORITHM,
+			KeyPair::Sr25519(_) => KB_JWT_ALGORITHM,
+		};
+		let header = JwtHeader { alg: alg.to_string(), typ: KB_JWT_TYPE.to_string(), kid: None };
+		let claims = KeyBindingClaims {
+			nonce: nonce.to_string(),
+			aud: aud.to_string(),
+			iat,
+			sd_hash: digest(&presentation.without_key_binding()),
+		};
+		let signing_input = format!("{}.{}", encode_segment(&header)?, encode_segment(&claims)?);
+		let signature = match holder {
+			KeyPair::Ed25519(pair) => pair.sign(signing_input.as_bytes()).0.to_vec(),
+			KeyPair::Sr25519(_) => holder.sign(signing_input.as_bytes()).encode(),
+		};
+		presentation.key_binding = Some(format!("{}.{}", signing_input, encode_base64(signature)));
+
+		Ok(presentation)
+	}
+
+	/// `<jwt>~<disclosure>~...~`, the input of the key binding `sd_hash`.
+	fn without_key_binding(&self) -> String {
+		let mut out = self.jwt.clone();
+		out.push('~');
+		for disclosure in &self.disclosures {
+			out.push_str(disclosure);
+			out.push('~');
+		}
+		out
+	}
+}
+
+impl fmt::Display for SdJwt {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}{}",
+			self.without_key_binding(),
+			self.key_binding.as_deref().unwrap_or_default()
+		)
+	}
+}
+
+/// base64url(sha256(`value`))
+fn digest(value: &str) -> String {
+	encode_base64(Sha256::digest(value.as_bytes()))
+}
+
+/// Verifies the issuer signature with `vc_pubkey` and the revealed disclosures against the signed
+/// digests. When `key_binding` is provided as `(nonce, aud)`, the presentation must also be bound
+/// to them by the credential subject. A key binding JWT is verified whenever it's presented: its
+/// signature, `sd_hash` and `iat`, which can't be in the future.
+pub fn verify_sd_jwt(
+	sd_jwt: &SdJwt,
+	vc_pubkey: &ed25519::Public,
+	key_binding: Option<(&str, &str)>,
+) -> Result<DisclosedCredential, JwtError> {
+	verify_sd_jwt_with(sd_jwt, vc_pubkey, key_binding, &TimePolicy::now())
+}
+
+/// Like `verify_sd_jwt`, the key binding `iat` is checked against `time`: it can't be in the
+/// future, nor older than `time.max_age` when set.
+pub fn verify_sd_jwt_with(
+	sd_jwt: &SdJwt,
+	vc_pubkey: &ed25519::Public,
+	key_binding: Option<(&str, &str)>,
+	time: &TimePolicy,
+) -> Result<DisclosedCredential, JwtError> {
+	let mut payload: Value = verify_jws(&sd_jwt.jwt, vc_pubkey)?;
+	if payload["_sd_alg"] != SD_ALG {
+		return Err(JwtError::InvalidDisclosure(format!(
+			"Unsupported _sd_alg {}",
+			payload["_sd_alg"]
+		)))
+	}
+
+	let mut revealed = HashMap::new();
+	for disclosure in &sd_jwt.disclosures {
+		let value = match decode_segment::<Value>(disclosure)? {
+			Value::Array(mut items) if items.len() == 2 => items.remove(1),
+			_ => return Err(JwtError::InvalidDisclosure(disclosure.clone())),
+		};
+		if revealed.insert(digest(disclosure), value).is_some() {
+			return Err(JwtError::InvalidDisclosure(format!("Duplicate disclosure {}", disclosure)))
+		}
+	}
+
+	let subject = payload["vc"]["credentialSubject"].take();
+	let mut subject = match subject {
+		Value::Object(subject) => subject,
+		_ => return Err(JwtError::Malformed("Missing credentialSubject".to_string())),
+	};
+	let mut fields = vec![];
+	for field in DISCLOSED_FIELDS {
+		let entries = subject.get(field).and_then(Value::as_array).cloned().unwrap_or_default();
+		let values: Vec<Option<Value>> = entries
+			.iter()
+			.map(|entry| entry["..."].as_str().and_then(|digest| revealed.remove(digest)))
+			.collect();
+		fields.push(values);
+	}
+	if !revealed.is_empty() {
+		return Err(JwtError::InvalidDisclosure("Disclosure not signed by the issuer".to_string()))
+	}
+
+	let (assertions, values) = (&fields[0], &fields[1]);
+	if assertions.len() != values.len() {
+		return Err(JwtError::InvalidDisclosure("assertions and values mismatch".to_string()))
+	}
+	let mut indexes = vec![];
+	let mut disclosed = (vec![], vec![]);
+	for (index, entry) in assertions.iter().zip(values).enumerate() {
+		match entry {
+			(Some(assertion), Some(value)) => {
+				indexes.push(index);
+				disclosed.0.push(assertion.clone());
+				disclosed.1.push(value.clone());
+			},
+			(None, None) => {},
+			_ =>
+				return Err(JwtError::InvalidDisclosure(format!("Partial disclosure of {}", index))),
+		}
+	}
+	subject.insert("assertions".to_string(), Value::Array(disclosed.0));
+	subject.insert("values".to_string(), Value::Array(disclosed.1));
+	payload["vc"]["credentialSubject"] = Value::Object(subject);
+
+	let claims: VcJwtClaims =
+		serde_json::from_value(payload).map_err(|e| JwtError::Malformed(format!("{:?}", e)))?;
+	let credential = claims.into_credential()?;
+
+	if key_binding.is_some() || sd_jwt.key_binding.is_some() {
+		verify_key_binding(sd_jwt, &credential, key_binding, time)?;
+	}
+
+	Ok(DisclosedCredential { credential, indexes })
+}
+
+/// `expected` is the `(nonce, aud)` of the verifier, they aren't checked without it.
+fn verify_key_binding(
+	sd_jwt: &SdJwt,
+	credential: &Credential,
+	expected: Option<(&str, &str)>,
+	time: &TimePolicy,
+) -> Result<(), JwtError> {
+	let invalid = |reason: &str| JwtError::InvalidKeyBinding(reason.to_string());
+
+	let key_binding = sd_jwt.key_binding.as_deref().ok_or_else(|| invalid("Missing"))?;
+	let (encoded_header, encoded_claims, encoded_signature) = split(key_binding)?;
+	let header: JwtHeader = decode_segment(encoded_header)?;
+	if header.typ != KB_JWT_TYPE || !matches!(header.alg.as_str(), JWT_ALGORITHM | KB_JWT_ALGORITHM)
+	{
+		return Err(invalid("Unsupported header"))
+	}
+
+	let claims: KeyBindingClaims = decode_segment(encoded_claims)?;
+	if let Some((nonce, aud)) = expected {
+		if claims.nonce != nonce {
+			return Err(invalid("nonce mismatch"))
+		}
+		if claims.aud != aud {
+			return Err(invalid("aud mismatch"))
+		}
+	}
+	let issued_at = claims.iat.saturating_mul(1000);
+	if issued_at > time.now.saturating_add(time.max_clock_skew) {
+		return Err(invalid("iat in the future"))
+	}
+	if let Some(max_age) = time.max_age {
+		if issued_at.saturating_add(max_age) < time.now.saturating_sub(time.max_clock_skew) {
+			return Err(invalid("iat too old"))
+		}
+	}
+	if claims.sd_hash != digest(&sd_jwt.without_key_binding()) {
+		return Err(invalid("sd_hash mismatch"))
+	}
+
+	let holder = subject_identity(&credential.credential_subject.id)
+		.ok_or_else(|| invalid("Invalid subject"))?;
+	let signature = decode_base64(encoded_signature)?;
+	let signing_input = format!("{}.{}", encoded_header, encoded_claims);
+	let verified = if header.alg == JWT_ALGORITHM {
+		let public = match &holder {
+			Identity::Substrate(address) => ed25519::Public::from_slice(address.as_ref()).ok(),
+			_ => None,
+		};
+		let signature = ed25519::Signature::from_slice(&signature);
+		match (signature, public) {
+			(Some(signature), Some(public)) =>
+				Ed25519Pair::verify(&signature, signing_input.as_bytes(), &public),
+			_ => false,
+		}
+	} else {
+		LitentryMultiSignature::decode(&mut signature.as_slice())
+			.map_err(|_| invalid("Invalid signature encoding"))?
+			.verify(signing_input.as_bytes(), &holder)
+	};
+	if !verified {
+		return Err(invalid("Not signed by the subject"))
+	}
+
+	Ok(())
+}
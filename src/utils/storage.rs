@@ -0,0 +1,163 @@
+use crate::primitives::{cerror::CError, CResult};
+use frame_metadata::{
+	v14::{PalletMetadata, StorageEntryMetadata, StorageEntryType, StorageHasher},
+	RuntimeMetadata, RuntimeMetadataPrefixed,
+};
+use scale_info::{form::PortableForm, TypeDef};
+use sp_core::hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64};
+
+/// A storage item resolved from the runtime metadata.
+/// Keeps everything we need to build its storage keys: the pallet storage prefix, the item name
+/// and one hasher for each map key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageEntry {
+	pub pallet_prefix: String,
+	pub item: String,
+	pub hashers: Vec<StorageHasher>,
+}
+
+impl StorageEntry {
+	/// `twox_128(pallet_prefix) ++ twox_128(item)`
+	/// This is the full key of a `StorageValue`, and the prefix shared by all keys of a map.
+	pub fn prefix(&self) -> Vec<u8> {
+		let mut bytes = twox_128(self.pallet_prefix.as_bytes()).to_vec();
+		bytes.extend(&twox_128(self.item.as_bytes())[..]);
+		bytes
+	}
+
+	pub fn value_key(&self) -> CResult<Vec<u8>> {
+		self.map_key(&[])
+	}
+
+	/// `encoded_keys` are the SCALE encoded map keys, one for each hasher.
+	pub fn map_key(&self, encoded_keys: &[Vec<u8>]) -> CResult<Vec<u8>> {
+		if encoded_keys.len() != self.hashers.len() {
+			return Err(CError::MetadataError(format!(
+				"{}::{} expects {} key(s), got {}",
+				self.pallet_prefix,
+				self.item,
+				self.hashers.len(),
+				encoded_keys.len()
+			)))
+		}
+
+		let mut bytes = self.prefix();
+		for (hasher, key) in self.hashers.iter().zip(encoded_keys) {
+			bytes.extend(hash_key(hasher, key));
+		}
+
+		Ok(bytes)
+	}
+}
+
+pub fn storage_entry(
+	metadata: &RuntimeMetadataPrefixed,
+	pallet: &str,
+	item: &str,
+) -> CResult<StorageEntry> {
+	let pallet_metadata = pallet_metadata(metadata, pallet)?;
+	let storage = pallet_metadata
+		.storage
+		.as_ref()
+		.ok_or_else(|| CError::StorageNotFound(pallet.to_string(), item.to_string()))?;
+	let entry: &StorageEntryMetadata<PortableForm> = storage
+		.entries
+		.iter()
+		.find(|entry| entry.name == item)
+		.ok_or_else(|| CError::StorageNotFound(pallet.to_string(), item.to_string()))?;
+
+	let hashers = match &entry.ty {
+		StorageEntryType::Plain(_) => vec![],
+		StorageEntryType::Map { hashers, .. } => hashers.clone(),
+	};
+
+	Ok(StorageEntry { pallet_prefix: storage.prefix.clone(), item: entry.name.clone(), hashers })
+}
+
+pub fn pallet_metadata<'a>(
+	metadata: &'a RuntimeMetadataPrefixed,
+	pallet: &str,
+) -> CResult<&'a PalletMetadata<PortableForm>> {
+	match &metadata.1 {
+		RuntimeMetadata::V14(metadata) => metadata
+			.pallets
+			.iter()
+			.find(|p| p.name == pallet)
+			.ok_or_else(|| CError::PalletNotFound(pallet.to_string())),
+		_ => Err(CError::MetadataError("Only metadata V14 is supported".to_string())),
+	}
+}
+
+/// `[pallet_index, call_index]` of `pallet::call`, the leading bytes of an encoded call.
+pub fn call_index(
+	metadata: &RuntimeMetadataPrefixed,
+	pallet: &str,
+	call: &str,
+) -> CResult<[u8; 2]> {
+	let RuntimeMetadata::V14(runtime_metadata) = &metadata.1 else {
+		return Err(CError::MetadataError("Only metadata V14 is supported".to_string()))
+	};
+
+	let pallet_metadata = pallet_metadata(metadata, pallet)?;
+	let call_not_found = || CError::MetadataError(format!("{}::{} not found", pallet, call));
+	let calls = pallet_metadata.calls.as_ref().ok_or_else(call_not_found)?;
+	let call_type = runtime_metadata.types.resolve(calls.ty.id).ok_or_else(call_not_found)?;
+
+	match &call_type.type_def {
+		TypeDef::Variant(variants) => variants
+			.variants
+			.iter()
+			.find(|variant| variant.name == call)
+			.map(|variant| [pallet_metadata.index, variant.index])
+			.ok_or_else(call_not_found),
+		_ => Err(call_not_found()),
+	}
+}
+
+pub fn hash_key(hasher: &StorageHasher, encoded_key: &[u8]) -> Vec<u8> {
+	match hasher {
+		StorageHasher::Blake2_128 => blake2_128(encoded_key).to_vec(),
+		StorageHasher::Blake2_256 => blake2_256(encoded_key).to_vec(),
+		StorageHasher::Blake2_128Concat => [&blake2_128(encoded_key)[..], encoded_key].concat(),
+		StorageHasher::Twox128 => twox_128(encoded_key).to_vec(),
+		StorageHasher::Twox256 => twox_256(encoded_key).to_vec(),
+		StorageHasher::Twox64Concat => [&twox_64(encoded_key)[..], encoded_key].concat(),
+		StorageHasher::Identity => encoded_key.to_vec(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::public_api::storage_value_key;
+
+	#[test]
+	fn storage_entry_prefix_works() {
+		let entry = StorageEntry {
+			pallet_prefix: "VCManagement".to_string(),
+			item: "VCRegistry".to_string(),
+			hashers: vec![StorageHasher::Blake2_128Concat],
+		};
+
+		assert_eq!(
+			hex::encode(entry.prefix()),
+			"b8806b89e4f9af656f87b35e6112ee1bda2e7b4c5a367debe17c26748ec6b3e6"
+		);
+		assert_eq!(entry.prefix(), storage_value_key("VCManagement", "VCRegistry"));
+	}
+
+	#[test]
+	fn map_key_checks_key_count() {
+		let entry = StorageEntry {
+			pallet_prefix: "Teerex".to_string(),
+			item: "EnclaveRegistry".to_string(),
+			hashers: vec![StorageHasher::Blake2_128Concat],
+		};
+
+		assert!(entry.value_key().is_err());
+
+		let key = entry.map_key(&[1u64.to_le_bytes().to_vec()]).unwrap();
+		assert_eq!(key.len(), 32 + 16 + 8);
+		assert_eq!(&key[48..], &1u64.to_le_bytes());
+	}
+}
@@ -1,9 +1,25 @@
-use crate::primitives::vc::{Credential, CredentialType};
+use crate::{
+	primitives::{
+		address::{Address20, Address32, Address33},
+		identity::Identity,
+		vc::{Credential, CredentialType, CredentialV2, Proof, Status, VCSchema},
+		AccountId, VCIndex,
+	},
+	utils::{
+		enclave_registry::EnclaveLookup,
+		public_api::mrenclave_from_bs58,
+		raw_json::remove_member,
+		vc_registry::{decode_vc_context, VCRegistryLookup},
+	},
+};
 use jsonschema::{Draft, JSONSchema};
+use log::debug;
 use sp_core::{
+	blake2_256,
 	ed25519::{self, Pair as Ed25519Pair},
-	Pair,
+	Pair, H256,
 };
+use std::{fmt, time::SystemTime};
 
 /**
  * Here are the key points that need to be verified for VC, and a large number of verifiable
@@ -16,33 +32,401 @@ use sp_core::{
 const CONTEXT: [&str; 2] =
 	["https://www.w3.org/2018/credentials/v1", "https://w3id.org/security/suites/ed25519-2020/v1"];
 
-pub fn verify_vc(vc_pubkey: &ed25519::Public, vc: &Credential) -> Result<bool, String> {
-	let verified_schema = verify_vc_schema(vc)?;
-	println!("Verify scheme: {verified_schema}");
+/// What a credential is verified against.
+/// The checks whose data isn't provided are reported as `Skipped`; `verified()` ignores them,
+/// `strict()` rejects them.
+pub struct VerifyContext<'a> {
+	pub vc_pubkey: ed25519::Public,
+	/// Enclaves the issuer is looked up in, either the live parachain or a snapshot.
+	pub enclaves: Option<&'a dyn EnclaveLookup>,
+	/// The identity the credential is expected to be issued to.
+	pub holder: Option<Identity>,
+	pub time: Option<TimePolicy>,
+	/// `VCRegistry` the credential status is looked up in.
+	pub registry: Option<&'a dyn VCRegistryLookup>,
+}
+
+impl<'a> VerifyContext<'a> {
+	pub fn new(vc_pubkey: ed25519::Public) -> Self {
+		Self { vc_pubkey, enclaves: None, holder: None, time: None, registry: None }
+	}
+
+	pub fn with_enclaves(mut self, enclaves: &'a dyn EnclaveLookup) -> Self {
+		self.enclaves = Some(enclaves);
+		self
+	}
+
+	/// `holder` can be an `Identity` or an `AccountId`.
+	pub fn with_holder(mut self, holder: impl Into<Identity>) -> Self {
+		self.holder = Some(holder.into());
+		self
+	}
+
+	pub fn with_time(mut self, time: TimePolicy) -> Self {
+		self.time = Some(time);
+		self
+	}
+
+	pub fn with_registry(mut self, registry: &'a dyn VCRegistryLookup) -> Self {
+		self.registry = Some(registry);
+		self
+	}
+}
+
+/// Time validity rules, all the values are unix timestamps in milliseconds like the credential
+/// timestamps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimePolicy {
+	/// The time the credential is verified at.
+	pub now: u64,
+	/// Tolerated difference between the issuer clock and `now`.
+	pub max_clock_skew: u64,
+	/// Credentials issued longer ago are rejected, even when they don't expire.
+	pub max_age: Option<u64>,
+}
+
+impl TimePolicy {
+	pub const DEFAULT_MAX_CLOCK_SKEW: u64 = 5 * 60 * 1000;
+
+	pub fn at(now: u64) -> Self {
+		Self { now, max_clock_skew: Self::DEFAULT_MAX_CLOCK_SKEW, max_age: None }
+	}
+
+	/// Verifies at the current system time.
+	pub fn now() -> Self {
+		let now = SystemTime::now()
+			.duration_since(SystemTime::UNIX_EPOCH)
+			.expect("Failed to retrieve timestamp")
+			.as_millis() as u64;
+
+		Self::at(now)
+	}
+
+	pub fn with_max_clock_skew(mut self, max_clock_skew: u64) -> Self {
+		self.max_clock_skew = max_clock_skew;
+		self
+	}
+
+	pub fn with_max_age(mut self, max_age: u64) -> Self {
+		self.max_age = Some(max_age);
+		self
+	}
+}
+
+pub fn verify_vc(vc_pubkey: &ed25519::Public, vc: &Credential) -> VerificationReport {
+	verify_vc_with(&VerifyContext::new(*vc_pubkey), vc)
+}
+
+pub fn verify_vc_with(ctx: &VerifyContext, vc: &Credential) -> VerificationReport {
+	let hashes: Vec<H256> = credential_hash(vc).into_iter().collect();
+	verify_vc_checks(ctx, vc, &hashes, None, || verify_vc_proof(&ctx.vc_pubkey, vc))
+}
+
+/// The checks `verify_vc_with` runs, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Check {
+	Schema,
+	Info,
+	Subject,
+	Issuer,
+	Proof,
+	Time,
+	Status,
+}
+
+impl Check {
+	pub const ALL: [Check; 7] = [
+		Check::Schema,
+		Check::Info,
+		Check::Subject,
+		Check::Issuer,
+		Check::Proof,
+		Check::Time,
+		Check::Status,
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Check::Schema => "schema",
+			Check::Info => "info",
+			Check::Subject => "subject",
+			Check::Issuer => "issuer",
+			Check::Proof => "proof",
+			Check::Time => "time",
+			Check::Status => "status",
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+	Pass,
+	Fail(String),
+	/// The check was not run, e.g. `VerifyContext` doesn't provide what it needs.
+	Skipped(String),
+}
+
+impl fmt::Display for CheckOutcome {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CheckOutcome::Pass => write!(f, "pass"),
+			CheckOutcome::Fail(reason) => write!(f, "fail ({})", reason),
+			CheckOutcome::Skipped(reason) => write!(f, "skipped ({})", reason),
+		}
+	}
+}
+
+/// The outcome of each `Check` of a credential.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+	pub checks: Vec<(Check, CheckOutcome)>,
+}
+
+impl VerificationReport {
+	fn record(&mut self, check: Check, outcome: CheckOutcome) {
+		debug!("Verify {}: {}", check.name(), outcome);
+		self.checks.push((check, outcome));
+	}
+
+	/// Every check failed with `reason`, when the credential couldn't be checked at all.
+	pub fn failed(reason: &str) -> Self {
+		let checks = Check::ALL
+			.iter()
+			.map(|check| (*check, CheckOutcome::Fail(reason.to_string())))
+			.collect();
+		Self { checks }
+	}
+
+	pub fn outcome(&self, check: Check) -> Option<&CheckOutcome> {
+		self.checks.iter().find(|(c, _)| *c == check).map(|(_, outcome)| outcome)
+	}
+
+	/// No check failed, skipped checks are tolerated.
+	pub fn is_verified(&self) -> bool {
+		self.verified().is_ok()
+	}
+
+	/// Fails with the failed checks.
+	pub fn verified(&self) -> Result<(), VerificationError> {
+		self.error(|outcome| matches!(outcome, CheckOutcome::Fail(_)))
+	}
+
+	/// Strict mode: every check must have been run and passed.
+	pub fn strict(&self) -> Result<(), VerificationError> {
+		self.error(|outcome| outcome != &CheckOutcome::Pass)
+	}
+
+	fn error(&self, rejected: impl Fn(&CheckOutcome) -> bool) -> Result<(), VerificationError> {
+		let checks: Vec<_> =
+			self.checks.iter().filter(|(_, outcome)| rejected(outcome)).cloned().collect();
+		if checks.is_empty() {
+			Ok(())
+		} else {
+			Err(VerificationError { checks })
+		}
+	}
+}
+
+/// The checks of a `VerificationReport` which were rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationError {
+	pub checks: Vec<(Check, CheckOutcome)>,
+}
+
+impl fmt::Display for VerificationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, (check, outcome)) in self.checks.iter().enumerate() {
+			if i > 0 {
+				write!(f, ", ")?;
+			}
+			write!(f, "{}: {}", check.name(), outcome)?;
+		}
+		Ok(())
+	}
+}
+
+/// A credential in either data model layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionedCredential {
+	V1(Credential),
+	V2(CredentialV2),
+}
+
+impl VersionedCredential {
+	/// The layout is picked from the `@context`.
+	pub fn from_json(json: &str) -> Result<Self, String> {
+		let value: serde_json::Value =
+			serde_json::from_str(json).map_err(|e| format!("{:?}", e))?;
+		let context: Vec<String> = value
+			.get("@context")
+			.and_then(|context| serde_json::from_value(context.clone()).ok())
+			.unwrap_or_default();
+
+		if CredentialV2::is_v2(&context) {
+			serde_json::from_value(value).map(Self::V2).map_err(|e| format!("{:?}", e))
+		} else {
+			serde_json::from_value(value).map(Self::V1).map_err(|e| format!("{:?}", e))
+		}
+	}
+
+	pub fn to_v1(&self) -> Result<Credential, String> {
+		match self {
+			Self::V1(vc) => Ok(vc.clone()),
+			Self::V2(vc) => Credential::try_from(vc.clone()),
+		}
+	}
+}
+
+/// A `CredentialV2` is checked as the `Credential` it converts to, except for its proof and
+/// `VCRegistry` hash which may have been computed over either layout.
+pub fn verify_versioned_vc_with(
+	ctx: &VerifyContext,
+	vc: &VersionedCredential,
+) -> Result<VerificationReport, String> {
+	match vc {
+		VersionedCredential::V1(vc) => Ok(verify_vc_with(ctx, vc)),
+		VersionedCredential::V2(vc_v2) => {
+			let vc = Credential::try_from(vc_v2.clone())?;
+			let hashes: Vec<H256> = [credential_hash(&vc), credential_hash_v2(vc_v2)]
+				.into_iter()
+				.flatten()
+				.collect();
+			Ok(verify_vc_checks(ctx, &vc, &hashes, None, || {
+				verify_vc_v2_proof(&ctx.vc_pubkey, vc_v2)
+			}))
+		},
+	}
+}
+
+/// `hashes` are the candidate `VCRegistry` hashes of the credential, `schema` the compiled
+/// `vc_schema()` when the caller keeps it around.
+/// The other checks are skipped when the credential doesn't match the schema.
+pub(crate) fn verify_vc_checks<F>(
+	ctx: &VerifyContext,
+	vc: &Credential,
+	hashes: &[H256],
+	schema: Option<&JSONSchema>,
+	verify_proof: F,
+) -> VerificationReport
+where
+	F: FnOnce() -> Result<bool, String>,
+{
+	let mut report = VerificationReport::default();
+
+	let verified_schema = match schema {
+		Some(schema) => verify_vc_schema_with(schema, vc),
+		None => verify_vc_schema(vc),
+	};
+	let schema = match verified_schema {
+		Ok(true) => CheckOutcome::Pass,
+		Ok(false) => CheckOutcome::Fail("doesn't match the credential schema".to_string()),
+		Err(e) => CheckOutcome::Fail(e),
+	};
+	let verified_schema = schema == CheckOutcome::Pass;
+	report.record(Check::Schema, schema);
 	if !verified_schema {
-		return Ok(verified_schema)
+		for check in &Check::ALL[1..] {
+			report.record(*check, CheckOutcome::Skipped("schema check failed".to_string()));
+		}
+		return report
 	}
 
-	let verified_vc_info = verify_vc_info(vc);
-	println!("Verify scheme: {verified_vc_info}");
+	let info = if verify_vc_info(vc) {
+		CheckOutcome::Pass
+	} else {
+		CheckOutcome::Fail("unexpected @context or type".to_string())
+	};
+	report.record(Check::Info, info);
 
-	let verified_subject = verify_vc_subject(vc);
-	println!("Verify scheme: {verified_subject}");
+	let subject = match verify_vc_subject(vc, ctx.holder.as_ref()) {
+		Ok(()) => CheckOutcome::Pass,
+		Err(errors) => CheckOutcome::Fail(format!("{:?}", errors)),
+	};
+	report.record(Check::Subject, subject);
 
-	let verified_issuer = verify_vc_issuer(vc);
-	println!("Verify scheme: {verified_issuer}");
+	let issuer = match ctx.enclaves.map(|enclaves| verify_vc_issuer(vc, enclaves)) {
+		Some(Ok(true)) => CheckOutcome::Pass,
+		Some(Ok(false)) => CheckOutcome::Fail("not issued by a registered enclave".to_string()),
+		Some(Err(e)) => CheckOutcome::Fail(e),
+		None => CheckOutcome::Skipped("no enclave registry".to_string()),
+	};
+	report.record(Check::Issuer, issuer);
 
-	let verified_proof = verify_vc_proof(vc_pubkey, vc)?;
-	println!("Verify scheme: {verified_proof}");
+	let proof = match verify_proof() {
+		Ok(true) => CheckOutcome::Pass,
+		Ok(false) => CheckOutcome::Fail("invalid signature".to_string()),
+		Err(e) => CheckOutcome::Fail(e),
+	};
+	report.record(Check::Proof, proof);
 
-	Ok(verified_schema && verified_vc_info && verified_subject && verified_issuer && verified_proof)
+	let time = match ctx.time.as_ref().map(|time| verify_vc_time(vc, time)) {
+		Some(Ok(())) => CheckOutcome::Pass,
+		Some(Err(errors)) => CheckOutcome::Fail(format!("{:?}", errors)),
+		None => CheckOutcome::Skipped("no time policy".to_string()),
+	};
+	report.record(Check::Time, time);
+
+	let status = match ctx.registry {
+		Some(registry) => {
+			let statuses: Vec<_> = hashes
+				.iter()
+				.map(|hash| verify_vc_status_with_hash(vc, *hash, registry))
+				.collect();
+			if statuses.contains(&Ok(CredentialStatus::Active)) {
+				CheckOutcome::Pass
+			} else {
+				CheckOutcome::Fail(format!("{:?}", statuses))
+			}
+		},
+		None => CheckOutcome::Skipped("no VCRegistry".to_string()),
+	};
+	report.record(Check::Status, status);
+
+	report
 }
 
 pub fn verify_vc_schema(vc: &Credential) -> Result<bool, String> {
-	let vc: serde_json::Value = serde_json::to_value(vc).map_err(|e| format!("{:?}", e))?;
+	verify_vc_schema_with(&vc_schema()?, vc)
+}
+
+/// The compiled `docs/templates/vc_schema.json`, compiling is much slower than validating so it
+/// should be reused across credentials.
+pub fn vc_schema() -> Result<JSONSchema, String> {
 	let schema = include_bytes!("../../docs/templates/vc_schema.json");
 	let schema: serde_json::Value =
 		serde_json::from_slice(schema).map_err(|e| format!("{:?}", e))?;
+
+	JSONSchema::options()
+		.with_draft(Draft::Draft202012)
+		.compile(&schema)
+		.map_err(|e| format!("{:?}", e))
+}
+
+pub fn verify_vc_schema_with(schema: &JSONSchema, vc: &Credential) -> Result<bool, String> {
+	let vc: serde_json::Value = serde_json::to_value(vc).map_err(|e| format!("{:?}", e))?;
+	Ok(schema.is_valid(&vc))
+}
+
+/// Verifies the credential against a schema fetched from the `VCManagement::SchemaRegistry`.
+/// The credential's `credential_schema` must point at `schema`, and the schema must be active.
+pub fn verify_vc_registered_schema(vc: &Credential, schema: &VCSchema) -> Result<bool, String> {
+	let credential_schema = vc
+		.credential_schema
+		.as_ref()
+		.ok_or_else(|| "credentialSchema is missing".to_string())?;
+	if credential_schema.id != schema.id() {
+		return Err(format!(
+			"credentialSchema {} doesn't match schema {}",
+			credential_schema.id,
+			schema.id()
+		))
+	}
+	if schema.status != Status::Active {
+		return Ok(false)
+	}
+
+	let vc: serde_json::Value = serde_json::to_value(vc).map_err(|e| format!("{:?}", e))?;
+	let schema = schema.content_json().map_err(|e| format!("{:?}", e))?;
 	let compiled_schema = JSONSchema::options()
 		.with_draft(Draft::Draft202012)
 		.compile(&schema)
@@ -62,22 +446,277 @@ pub fn verify_vc_info(vc: &Credential) -> bool {
 	verified_context && verified_types
 }
 
-/// TODO:
-/// This data structure is the main content of VC, including the core content of assertion.
-/// How to verify this part accurately and effectively?
-pub fn verify_vc_subject(_vc: &Credential) -> bool {
-	true
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SubjectError {
+	/// `credential_subject.id` is neither a DID nor the hex of an account.
+	InvalidSubjectId(String),
+	/// The subject is not the expected holder.
+	HolderMismatch {
+		expected: Identity,
+		subject: Identity,
+	},
+	/// Each assertion must have exactly one value.
+	ValuesLengthMismatch {
+		assertions: usize,
+		values: usize,
+	},
+	InvalidEndpoint(String),
+}
+
+/// Checks the `credential_subject`, all the checks are run and the failed ones are returned.
+/// * `id` decodes to an `Identity`, and to `holder` when provided.
+/// * `assertions` and `values` have the same length.
+/// * `endpoint` is a http(s) or ws(s) URL.
+pub fn verify_vc_subject(
+	vc: &Credential,
+	holder: Option<&Identity>,
+) -> Result<(), Vec<SubjectError>> {
+	let subject = &vc.credential_subject;
+	let mut errors = vec![];
+
+	match subject_identity(&subject.id) {
+		Some(identity) =>
+			if let Some(holder) = holder {
+				if holder != &identity {
+					errors.push(SubjectError::HolderMismatch {
+						expected: holder.clone(),
+						subject: identity,
+					});
+				}
+			},
+		None => errors.push(SubjectError::InvalidSubjectId(subject.id.clone())),
+	}
+
+	if subject.assertions.len() != subject.values.len() {
+		errors.push(SubjectError::ValuesLengthMismatch {
+			assertions: subject.assertions.len(),
+			values: subject.values.len(),
+		});
+	}
+
+	if !is_valid_endpoint(&subject.endpoint) {
+		errors.push(SubjectError::InvalidEndpoint(subject.endpoint.clone()));
+	}
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+/// `id` is either a `did:litentry:` DID, or the (optionally 0x prefixed) hex of a substrate (32
+/// bytes), evm (20 bytes) or bitcoin (33 bytes) account.
+pub fn subject_identity(id: &str) -> Option<Identity> {
+	if id.starts_with("did:") {
+		return Identity::from_did(id).ok()
+	}
+
+	let bytes = hex::decode(id.trim_start_matches("0x")).ok()?;
+	match bytes.len() {
+		32 => Address32::try_from(bytes.as_slice()).ok().map(Identity::Substrate),
+		20 => Address20::try_from(bytes.as_slice()).ok().map(Identity::Evm),
+		33 => Address33::try_from(bytes.as_slice()).ok().map(Identity::Bitcoin),
+		_ => None,
+	}
+}
+
+fn is_valid_endpoint(endpoint: &str) -> bool {
+	match url::Url::parse(endpoint) {
+		Ok(url) =>
+			matches!(url.scheme(), "http" | "https" | "ws" | "wss") && url.host_str().is_some(),
+		Err(_) => false,
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeError {
+	/// `issuance_timestamp` is in the future.
+	NotYetValid {
+		issued_at: u64,
+	},
+	/// `proof.created_timestamp` is in the future.
+	ProofCreatedInFuture {
+		created_at: u64,
+	},
+	Expired {
+		expired_at: u64,
+	},
+	/// Issued longer ago than `TimePolicy::max_age`.
+	TooOld {
+		issued_at: u64,
+	},
+}
+
+/// Checks the credential timestamps against `time`, the failed checks are returned.
+pub fn verify_vc_time(vc: &Credential, time: &TimePolicy) -> Result<(), Vec<TimeError>> {
+	let latest = time.now.saturating_add(time.max_clock_skew);
+	let earliest = time.now.saturating_sub(time.max_clock_skew);
+	let mut errors = vec![];
+
+	if vc.issuance_timestamp > latest {
+		errors.push(TimeError::NotYetValid { issued_at: vc.issuance_timestamp });
+	}
+
+	if let Some(proof) = &vc.proof {
+		if proof.created_timestamp > latest {
+			errors.push(TimeError::ProofCreatedInFuture { created_at: proof.created_timestamp });
+		}
+	}
+
+	if let Some(expiration_timestamp) = vc.expiration_timestamp {
+		if expiration_timestamp < earliest {
+			errors.push(TimeError::Expired { expired_at: expiration_timestamp });
+		}
+	}
+
+	if let Some(max_age) = time.max_age {
+		if vc.issuance_timestamp.saturating_add(max_age) < earliest {
+			errors.push(TimeError::TooOld { issued_at: vc.issuance_timestamp });
+		}
+	}
+
+	if errors.is_empty() {
+		Ok(())
+	} else {
+		Err(errors)
+	}
+}
+
+/// The issuer must be an enclave known by `enclaves`:
+/// * `issuer.mrenclave` is the (base58) mrenclave of the enclave,
+/// * `issuer.id` is the enclave account,
+/// * `proof.verification_method` is the enclave `vc_pubkey`.
+/// Several enclaves can share the same mrenclave, it's enough for one of them to match.
+pub fn verify_vc_issuer(vc: &Credential, enclaves: &dyn EnclaveLookup) -> Result<bool, String> {
+	let mrenclave = match mrenclave_from_bs58(vc.issuer.mrenclave.clone()) {
+		Some(mrenclave) => mrenclave,
+		None => return Ok(false),
+	};
+	let verification_method = match &vc.proof {
+		Some(proof) => normalize_hex(&proof.verification_method),
+		None => return Ok(false),
+	};
+	let issuer_id = normalize_hex(&vc.issuer.id);
+
+	let verified = enclaves
+		.enclaves_by_mrenclave(&mrenclave)
+		.map_err(|e| format!("{:?}", e))?
+		.iter()
+		.any(|enclave| {
+			let vc_pubkey = enclave.vc_pubkey.as_ref().map(hex::encode);
+			hex::encode(&enclave.pubkey) == issuer_id &&
+				vc_pubkey.as_ref() == Some(&verification_method)
+		});
+
+	Ok(verified)
 }
 
-/// TODO:
-/// Here is the RA related verification
-pub fn verify_vc_issuer(_vc: &Credential) -> bool {
-	true
+fn normalize_hex(hex_str: &str) -> String {
+	hex_str.trim_start_matches("0x").to_lowercase()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CredentialStatus {
+	Active,
+	Disabled,
+	/// Not in the `VCRegistry`, either never registered or revoked.
+	Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatusError {
+	/// The credential `id` is not a `VCIndex`.
+	InvalidId(String),
+	Lookup(String),
+	/// The entry exists, but its status can't be decoded.
+	UndecodableStatus,
+	HashMismatch {
+		registered: H256,
+		computed: H256,
+	},
+	SubjectMismatch {
+		registered: AccountId,
+		subject: String,
+	},
+}
+
+/// `blake2_256` of the JSON credential, the same way the worker computes the `VCContext` hash.
+/// The worker hashes the JSON string it returned, so the hash can only match when the credential
+/// serialises back to the same bytes, see `credential_hash_raw` otherwise.
+pub fn credential_hash(vc: &Credential) -> Result<H256, String> {
+	let json = serde_json::to_string(vc).map_err(|e| format!("{:?}", e))?;
+	Ok(credential_hash_raw(json.as_bytes()))
+}
+
+pub fn credential_hash_v2(vc: &CredentialV2) -> Result<H256, String> {
+	let json = serde_json::to_string(vc).map_err(|e| format!("{:?}", e))?;
+	Ok(credential_hash_raw(json.as_bytes()))
+}
+
+pub fn credential_hash_raw(json: &[u8]) -> H256 {
+	H256(blake2_256(json))
+}
+
+pub fn verify_vc_status(
+	vc: &Credential,
+	registry: &dyn VCRegistryLookup,
+) -> Result<CredentialStatus, StatusError> {
+	let hash = credential_hash(vc).map_err(StatusError::Lookup)?;
+	verify_vc_status_with_hash(vc, hash, registry)
+}
+
+/// Looks up the `VCContext` of the credential `id` and checks that it was registered for this
+/// credential (`hash`) and subject.
+pub fn verify_vc_status_with_hash(
+	vc: &Credential,
+	hash: H256,
+	registry: &dyn VCRegistryLookup,
+) -> Result<CredentialStatus, StatusError> {
+	let index = vc_index(&vc.id).ok_or_else(|| StatusError::InvalidId(vc.id.clone()))?;
+	let encoded = match registry.lookup_vc_context(&index) {
+		Ok(Some(encoded)) => encoded,
+		Ok(None) => return Ok(CredentialStatus::Unknown),
+		Err(e) => return Err(StatusError::Lookup(format!("{:?}", e))),
+	};
+
+	let (subject, registered_hash, status) = match decode_vc_context(&encoded) {
+		Ok(context) => (Some(context.subject), Some(context.hash), Some(context.status)),
+		Err(context) => (context.subject, context.hash, context.status),
+	};
+
+	if let Some(registered) = registered_hash {
+		if registered != hash {
+			return Err(StatusError::HashMismatch { registered, computed: hash })
+		}
+	}
+
+	// EVM and Bitcoin subjects are registered with the account they are mapped to.
+	if let Some(registered) = subject {
+		let subject = subject_identity(&vc.credential_subject.id).and_then(|i| i.to_account_id());
+		if subject != Some(registered.clone()) {
+			return Err(StatusError::SubjectMismatch {
+				registered,
+				subject: vc.credential_subject.id.clone(),
+			})
+		}
+	}
+
+	match status {
+		Some(Status::Active) => Ok(CredentialStatus::Active),
+		Some(Status::Disabled) => Ok(CredentialStatus::Disabled),
+		None => Err(StatusError::UndecodableStatus),
+	}
+}
+
+fn vc_index(id: &str) -> Option<VCIndex> {
+	let bytes = hex::decode(id.trim_start_matches("0x")).ok()?;
+	(bytes.len() == 32).then(|| VCIndex::from_slice(&bytes))
 }
 
 pub fn verify_vc_proof(vc_pubkey: &ed25519::Public, vc: &Credential) -> Result<bool, String> {
 	let mut value = serde_json::to_value(vc).map_err(|e| format!("{:?}", e))?;
-	let proof = vc.proof.clone().expect("Proof");
+	let proof = vc.proof.clone().ok_or_else(|| "Proof is missing".to_string())?;
 	let sig = proof.proof_value;
 	let sig = hex::decode(sig).map_err(|e| format!("{:?}", e))?;
 
@@ -91,3 +730,58 @@ pub fn verify_vc_proof(vc_pubkey: &ed25519::Public, vc: &Credential) -> Result<b
 
 	Ok(Ed25519Pair::verify(&signature, message, vc_pubkey))
 }
+
+/// Verifies the proof over `json` as the worker returned it, the signed message is `json` without
+/// its `proof` member. Unlike `verify_vc_proof`, it doesn't rely on `Credential` serialising back
+/// to the same bytes, e.g. when the worker wrote unknown members before known ones.
+pub fn verify_vc_proof_raw(vc_pubkey: &ed25519::Public, json: &[u8]) -> Result<bool, String> {
+	let (message, proof) =
+		remove_member(json, "proof")?.ok_or_else(|| "Proof is missing".to_string())?;
+	let proof: Proof = serde_json::from_slice(proof).map_err(|e| format!("{:?}", e))?;
+	let sig = hex::decode(proof.proof_value).map_err(|e| format!("{:?}", e))?;
+	let signature =
+		ed25519::Signature::from_slice(&sig).ok_or_else(|| "signature error.".to_string())?;
+
+	Ok(Ed25519Pair::verify(&signature, message, vc_pubkey))
+}
+
+/// `verify_vc_with` of the credential JSON returned by the worker, the proof and `VCRegistry`
+/// hash are checked against `json` itself.
+pub fn verify_vc_raw_with(ctx: &VerifyContext, json: &[u8]) -> Result<VerificationReport, String> {
+	verify_vc_raw_checks(ctx, json, None)
+}
+
+/// `verify_vc_raw_with` with the compiled `vc_schema()` when the caller keeps it around.
+pub(crate) fn verify_vc_raw_checks(
+	ctx: &VerifyContext,
+	json: &[u8],
+	schema: Option<&JSONSchema>,
+) -> Result<VerificationReport, String> {
+	let vc: Credential = serde_json::from_slice(json).map_err(|e| format!("{:?}", e))?;
+	let hashes: Vec<H256> = [Ok(credential_hash_raw(json)), credential_hash(&vc)]
+		.into_iter()
+		.flatten()
+		.collect();
+
+	Ok(verify_vc_checks(ctx, &vc, &hashes, schema, || verify_vc_proof_raw(&ctx.vc_pubkey, json)))
+}
+
+/// The proof of a `CredentialV2` either signs the v2 layout, or the `Credential` it was converted
+/// from.
+pub fn verify_vc_v2_proof(vc_pubkey: &ed25519::Public, vc: &CredentialV2) -> Result<bool, String> {
+	let proof = vc.proof.clone().ok_or_else(|| "Proof is missing".to_string())?;
+	let sig = hex::decode(proof.proof_value).map_err(|e| format!("{:?}", e))?;
+	let signature =
+		ed25519::Signature::from_slice(&sig).ok_or_else(|| "signature error.".to_string())?;
+
+	let mut unsigned = vc.clone();
+	unsigned.proof = None;
+	let message = serde_json::to_string(&unsigned).map_err(|e| format!("{:?}", e))?;
+	if Ed25519Pair::verify(&signature, message, vc_pubkey) {
+		return Ok(true)
+	}
+
+	let unsigned = Credential::try_from(unsigned)?;
+	let message = serde_json::to_string(&unsigned).map_err(|e| format!("{:?}", e))?;
+	Ok(Ed25519Pair::verify(&signature, message, vc_pubkey))
+}
@@ -0,0 +1,298 @@
+use crate::{
+	primitives::{
+		assertion::Assertion,
+		identity::Identity,
+		vc::{
+			AssertionLogic, Credential, CredentialSubject, CredentialType, Issuer, Op, Proof,
+			ProofType, CREDENTIALS_V1_CONTEXT,
+		},
+		MrEnclave, VCIndex,
+	},
+	utils::{
+		hex::hex_encode, json_ld::ED25519_2020_CONTEXT, public_api::mrenclave_to_bs58,
+		vc::TimePolicy,
+	},
+};
+use rand::Rng;
+use sp_core::{ed25519::Pair as Ed25519Pair, Pair};
+
+pub const DEFAULT_ISSUER_NAME: &str = "Litentry TEE Worker";
+pub const DEFAULT_ENDPOINT: &str = "wss://rpc.litentry-parachain.litentry.io";
+
+/// Builds the credential a worker would issue for an `Assertion`, e.g. for test fixtures or self
+/// issued credentials.
+/// The subject type, description, tags and assertion logic are filled in from the assertion
+/// (see `docs/vc-examples`), the parts of the logic which depend on the fetched data can't be and
+/// are left to `with_assertion`, e.g. the dates of A4. The follower and transaction ranges of A6
+/// and A8 are the ones of their example. Each assertion holds (`true`) unless `with_values` says
+/// otherwise.
+/// Only A1 to A11 have a known subject type, description and tags, they have to be set with
+/// `with_subject_info` for the other assertions. A5 has an example but is no longer an `Assertion`.
+#[derive(Clone, Debug)]
+pub struct CredentialBuilder {
+	id: Option<VCIndex>,
+	subject: CredentialSubject,
+	issuer_id: Option<String>,
+	mrenclave: MrEnclave,
+	values: Option<Vec<bool>>,
+	issuance_timestamp: Option<u64>,
+	expiration_timestamp: Option<u64>,
+}
+
+impl CredentialBuilder {
+	pub fn new(assertion: &Assertion, subject: &Identity) -> Self {
+		let (credential_type, description, tags) = subject_info(assertion);
+
+		Self {
+			id: None,
+			subject: CredentialSubject {
+				id: subject_id(subject),
+				description: description.to_string(),
+				types: credential_type.to_string(),
+				tag: tags.iter().map(|tag| tag.to_string()).collect(),
+				data_source: None,
+				assertions: assertion_logic(assertion),
+				values: vec![],
+				endpoint: DEFAULT_ENDPOINT.to_string(),
+				extra: Default::default(),
+			},
+			issuer_id: None,
+			mrenclave: MrEnclave::default(),
+			values: None,
+			issuance_timestamp: None,
+			expiration_timestamp: None,
+		}
+	}
+
+	/// The `VCIndex` of the credential, random by default.
+	pub fn with_id(mut self, id: VCIndex) -> Self {
+		self.id = Some(id);
+		self
+	}
+
+	/// The enclave issuing the credential, by default the signing key and a zero mrenclave.
+	pub fn with_issuer(mut self, issuer_id: &[u8], mrenclave: &MrEnclave) -> Self {
+		self.issuer_id = Some(hex::encode(issuer_id));
+		self.mrenclave = *mrenclave;
+		self
+	}
+
+	/// `credentialSubject` `type`, `description` and `tag`.
+	pub fn with_subject_info(
+		mut self,
+		credential_type: &str,
+		description: &str,
+		tags: &[&str],
+	) -> Self {
+		self.subject.types = credential_type.to_string();
+		self.subject.description = description.to_string();
+		self.subject.tag = tags.iter().map(|tag| tag.to_string()).collect();
+		self
+	}
+
+	/// Adds `assertion` to the `and` of the assertion logic, e.g. the `$from_date` and `$to_date`
+	/// items of A4 (see `docs/vc-examples/a4.json`). The `and` is created when the assertion has no
+	/// logic of its own.
+	pub fn with_assertion(mut self, assertion: AssertionLogic) -> Self {
+		match self.subject.assertions.first_mut() {
+			Some(AssertionLogic::And { items }) => items.push(Box::new(assertion)),
+			_ => self.subject.assertions.push(and(vec![assertion])),
+		}
+		self
+	}
+
+	/// One value per top level assertion logic, `sign` fails otherwise.
+	pub fn with_values(mut self, values: Vec<bool>) -> Self {
+		self.values = Some(values);
+		self
+	}
+
+	pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+		self.subject.endpoint = endpoint.to_string();
+		self
+	}
+
+	/// Unix timestamp in milliseconds, the current time by default.
+	pub fn issued_at(mut self, issuance_timestamp: u64) -> Self {
+		self.issuance_timestamp = Some(issuance_timestamp);
+		self
+	}
+
+	/// Unix timestamp in milliseconds, the credential doesn't expire by default.
+	pub fn expires_at(mut self, expiration_timestamp: u64) -> Self {
+		self.expiration_timestamp = Some(expiration_timestamp);
+		self
+	}
+
+	/// Signs the credential the way `verify_vc_proof` verifies it: the signature is over the JSON
+	/// of the credential without its `proof`.
+	pub fn sign(self, pair: &Ed25519Pair) -> Result<Credential, String> {
+		let public = pair.public();
+		let issuance_timestamp = self.issuance_timestamp.unwrap_or_else(|| TimePolicy::now().now);
+
+		let mut subject = self.subject;
+		if subject.types.is_empty() || subject.description.is_empty() {
+			return Err("The subject type and description have to be set with `with_subject_info`"
+				.to_string())
+		}
+		if subject.assertions.is_empty() {
+			return Err("The assertion logic has to be set with `with_assertion`".to_string())
+		}
+		subject.values = match self.values {
+			Some(values) if values.len() != subject.assertions.len() =>
+				return Err(format!(
+					"{} values for {} assertions",
+					values.len(),
+					subject.assertions.len()
+				)),
+			Some(values) => values,
+			None => vec![true; subject.assertions.len()],
+		};
+		let id = self.id.unwrap_or_else(|| VCIndex::from(rand::thread_rng().gen::<[u8; 32]>()));
+
+		let mut vc = Credential {
+			context: vec![CREDENTIALS_V1_CONTEXT.to_string(), ED25519_2020_CONTEXT.to_string()],
+			id: hex_encode(id.as_bytes()),
+			types: vec![CredentialType::VerifiableCredential],
+			credential_subject: subject,
+			issuer: Issuer {
+				id: self.issuer_id.unwrap_or_else(|| hex::encode(public.0)),
+				name: DEFAULT_ISSUER_NAME.to_string(),
+				mrenclave: mrenclave_to_bs58(&self.mrenclave),
+				extra: Default::default(),
+			},
+			issuance_timestamp,
+			expiration_timestamp: self.expiration_timestamp,
+			proof: None,
+			credential_schema: None,
+			extra: Default::default(),
+		};
+
+		let message = serde_json::to_string(&vc).map_err(|e| format!("{:?}", e))?;
+		let signature = pair.sign(message.as_bytes());
+		vc.proof = Some(Proof {
+			created_timestamp: issuance_timestamp,
+			proof_type: ProofType::Ed25519Signature2020,
+			proof_purpose: "assertionMethod".to_string(),
+			proof_value: hex::encode(signature.0),
+			verification_method: hex::encode(public.0),
+			extra: Default::default(),
+		});
+
+		Ok(vc)
+	}
+}
+
+/// Web3 subjects are identified by the hex of their account like the worker does, web2 ones by
+/// their DID.
+fn subject_id(subject: &Identity) -> String {
+	match subject {
+		Identity::Substrate(address) => hex::encode(address.as_ref()),
+		Identity::Evm(address) => hex::encode(address.as_ref()),
+		Identity::Bitcoin(address) => hex::encode(address.as_ref()),
+		_ => subject.to_did().unwrap_or_default(),
+	}
+}
+
+/// `credentialSubject` `type`, `description` and `tag`.
+fn subject_info(assertion: &Assertion) -> (&'static str, &'static str, &'static [&'static str]) {
+	match assertion {
+		Assertion::A1 => (
+			"Basic Identity Verification",
+			"The user has verified one identity in Web 2 and one identity in Web 3",
+			&["Litentry Network"],
+		),
+		Assertion::A2(..) => (
+			"Discord ID-Hubber Role Verification",
+			"The user has obtained an ID-Hubber role in a Litentry Discord channel",
+			&["Discord"],
+		),
+		Assertion::A3(..) => (
+			"Discord Member Verification",
+			"The user has commented in a specific Discord channel with a specific role",
+			&["Discord"],
+		),
+		Assertion::A4(..) => (
+			"LIT Holding Assertion",
+			"Since when has the user been consistently holding a min amount {x} of LIT token",
+			&["Ethereum", "Litmus", "Litentry"],
+		),
+		Assertion::A6 => (
+			"Twitter Follower Amount",
+			"The range of the user's Twitter follower count",
+			&["Twitter"],
+		),
+		Assertion::A7(..) => (
+			"DOT Holding Assertion",
+			"Since when has the user been consistently holding a min amount {x} of DOT token",
+			&["Polkadot"],
+		),
+		Assertion::A8(..) => (
+			"EVM/Substrate Transaction Count on Networks",
+			"The total amount of transaction the user has ever made in each of the available  networks (including invalid transactions)",
+			&["Litentry", "Litmus", "Polkadot", "Kusama", "Ethereum", "Khala"],
+		),
+		Assertion::A10(..) => (
+			"WBTC Holding Assertion",
+			"Since when has the user been consistently holding a min amount {x} of WBTC token",
+			&["Ethereum"],
+		),
+		Assertion::A11(..) => (
+			"ETH Holding Assertion",
+			"Since when has the user been consistently holding a min amount {x} of ETH token",
+			&["Ethereum"],
+		),
+		_ => ("", "", &[]),
+	}
+}
+
+/// The part of the assertion logic which only depends on the assertion parameters.
+fn assertion_logic(assertion: &Assertion) -> Vec<AssertionLogic> {
+	let logic = match assertion {
+		Assertion::A1 => and(vec![
+			item("$has_web2_account", Op::Equal, "true"),
+			item("$has_web3_account", Op::Equal, "true"),
+		]),
+		Assertion::A2(guild_id) => and(vec![
+			item("$verified_discord_account", Op::GreaterThan, "0"),
+			item("$has_joined", Op::Equal, "true"),
+			item("$discord_guild_id", Op::Equal, guild_id),
+		]),
+		Assertion::A3(guild_id, channel_id, role_id) => and(vec![
+			item("$has_role", Op::Equal, "true"),
+			item("$has_commented", Op::Equal, "true"),
+			item("$discord_guild_id", Op::Equal, guild_id),
+			item("$discord_channel_id", Op::Equal, channel_id),
+			item("$discord_role_id", Op::Equal, role_id),
+		]),
+		Assertion::A4(minimum_amount) |
+		Assertion::A7(minimum_amount) |
+		Assertion::A10(minimum_amount) |
+		Assertion::A11(minimum_amount) => and(vec![item("$minimum_amount", Op::Equal, minimum_amount)]),
+		Assertion::A6 => and(vec![
+			item("$total_followers", Op::GreaterThan, "0"),
+			item("$total_followers", Op::LessEq, "1"),
+		]),
+		Assertion::A8(networks) => and(vec![
+			item("$total_txs", Op::GreaterEq, "0"),
+			item("$total_txs", Op::LessThan, "1"),
+			AssertionLogic::Or {
+				items: networks
+					.iter()
+					.map(|network| Box::new(item("$network", Op::Equal, &format!("{:?}", network))))
+					.collect(),
+			},
+		]),
+		_ => return vec![],
+	};
+
+	vec![logic]
+}
+
+fn item(src: &str, op: Op, dst: &str) -> AssertionLogic {
+	AssertionLogic::Item { src: src.to_string(), op, dst: dst.to_string() }
+}
+
+fn and(items: Vec<AssertionLogic>) -> AssertionLogic {
+	AssertionLogic::And { items: items.into_iter().map(Box::new).collect() }
+}
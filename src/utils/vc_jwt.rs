@@ -0,0 +1,181 @@
+use crate::primitives::vc::Credential;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_core::{
+	ed25519::{self, Pair as Ed25519Pair},
+	Pair,
+};
+
+/// VC-JWT (https://www.w3.org/TR/vc-data-model/#json-web-token) form of a `Credential`, signed
+/// with EdDSA (Ed25519).
+pub const JWT_ALGORITHM: &str = "EdDSA";
+pub const JWT_TYPE: &str = "JWT";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JwtError {
+	/// Not made of three base64url segments, or a segment isn't the expected JSON.
+	Malformed(String),
+	UnsupportedAlgorithm(String),
+	InvalidSignature,
+	/// A selective disclosure doesn't match the signed digests, see `sd_jwt`.
+	InvalidDisclosure(String),
+	/// The key binding JWT of a selective disclosure presentation is invalid.
+	InvalidKeyBinding(String),
+	/// A registered claim doesn't match the embedded credential.
+	ClaimMismatch {
+		claim: &'static str,
+		expected: String,
+		found: String,
+	},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct JwtHeader {
+	pub alg: String,
+	pub typ: String,
+	/// Hex of the issuer `vc_pubkey`, like `Proof::verification_method`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub kid: Option<String>,
+}
+
+/// The registered claims are in seconds, while the embedded credential keeps its millisecond
+/// timestamps so that the conversion is lossless.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VcJwtClaims {
+	/// `issuer.id`
+	pub iss: String,
+	/// `credential_subject.id`
+	pub sub: String,
+	/// `issuance_timestamp`
+	pub nbf: u64,
+	/// `expiration_timestamp`
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub exp: Option<u64>,
+	/// `id`
+	pub jti: String,
+	/// The credential without its proof, the JWS is the proof.
+	pub vc: Credential,
+}
+
+impl VcJwtClaims {
+	pub fn from_credential(vc: &Credential) -> Self {
+		let mut vc = vc.clone();
+		vc.proof = None;
+
+		Self {
+			iss: vc.issuer.id.clone(),
+			sub: vc.credential_subject.id.clone(),
+			nbf: vc.issuance_timestamp / 1000,
+			exp: vc.expiration_timestamp.map(|exp| exp / 1000),
+			jti: vc.id.clone(),
+			vc,
+		}
+	}
+
+	/// The embedded credential, once checked against the registered claims.
+	pub fn into_credential(self) -> Result<Credential, JwtError> {
+		let expected = Self::from_credential(&self.vc);
+		check_claim("iss", &expected.iss, &self.iss)?;
+		check_claim("sub", &expected.sub, &self.sub)?;
+		check_claim("nbf", &expected.nbf, &self.nbf)?;
+		check_claim("exp", &expected.exp, &self.exp)?;
+		check_claim("jti", &expected.jti, &self.jti)?;
+
+		Ok(self.vc)
+	}
+}
+
+fn check_claim<T: PartialEq + std::fmt::Debug>(
+	claim: &'static str,
+	expected: &T,
+	found: &T,
+) -> Result<(), JwtError> {
+	if expected != found {
+		return Err(JwtError::ClaimMismatch {
+			claim,
+			expected: format!("{:?}", expected),
+			found: format!("{:?}", found),
+		})
+	}
+
+	Ok(())
+}
+
+/// Encodes `vc` as a compact JWS signed by `pair`, any existing proof is dropped.
+pub fn encode_vc_jwt(vc: &Credential, pair: &Ed25519Pair) -> Result<String, JwtError> {
+	sign_jws(&VcJwtClaims::from_credential(vc), pair)
+}
+
+/// Decodes a VC-JWT without verifying its signature.
+pub fn decode_vc_jwt(jwt: &str) -> Result<(JwtHeader, VcJwtClaims), JwtError> {
+	let (header, claims, _) = split(jwt)?;
+	Ok((decode_segment(header)?, decode_segment(claims)?))
+}
+
+/// Verifies the signature of a VC-JWT with `vc_pubkey` and returns the credential it carries,
+/// without proof.
+pub fn verify_vc_jwt(jwt: &str, vc_pubkey: &ed25519::Public) -> Result<Credential, JwtError> {
+	let claims: VcJwtClaims = verify_jws(jwt, vc_pubkey)?;
+	claims.into_credential()
+}
+
+/// EdDSA compact JWS of `claims`
+pub(crate) fn sign_jws<T: Serialize>(claims: &T, pair: &Ed25519Pair) -> Result<String, JwtError> {
+	let header = JwtHeader {
+		alg: JWT_ALGORITHM.to_string(),
+		typ: JWT_TYPE.to_string(),
+		kid: Some(hex::encode(pair.public().0)),
+	};
+
+	let signing_input = format!("{}.{}", encode_segment(&header)?, encode_segment(claims)?);
+	let signature = pair.sign(signing_input.as_bytes());
+
+	Ok(format!("{}.{}", signing_input, encode_base64(signature.0)))
+}
+
+/// The claims of an EdDSA compact JWS, once its signature is verified.
+pub(crate) fn verify_jws<T: DeserializeOwned>(
+	jwt: &str,
+	vc_pubkey: &ed25519::Public,
+) -> Result<T, JwtError> {
+	let (encoded_header, encoded_claims, encoded_signature) = split(jwt)?;
+	let header: JwtHeader = decode_segment(encoded_header)?;
+	if header.alg != JWT_ALGORITHM {
+		return Err(JwtError::UnsupportedAlgorithm(header.alg))
+	}
+
+	let signature = decode_base64(encoded_signature)?;
+	let signature = ed25519::Signature::from_slice(&signature).ok_or(JwtError::InvalidSignature)?;
+	let signing_input = format!("{}.{}", encoded_header, encoded_claims);
+	if !Ed25519Pair::verify(&signature, signing_input, vc_pubkey) {
+		return Err(JwtError::InvalidSignature)
+	}
+
+	decode_segment(encoded_claims)
+}
+
+pub(crate) fn split(jwt: &str) -> Result<(&str, &str, &str), JwtError> {
+	let segments: Vec<&str> = jwt.split('.').collect();
+	match segments[..] {
+		[header, claims, signature] => Ok((header, claims, signature)),
+		_ => Err(JwtError::Malformed(format!("{} segments", segments.len()))),
+	}
+}
+
+pub(crate) fn encode_segment<T: Serialize>(value: &T) -> Result<String, JwtError> {
+	let json = serde_json::to_vec(value).map_err(|e| JwtError::Malformed(format!("{:?}", e)))?;
+	Ok(encode_base64(json))
+}
+
+pub(crate) fn decode_segment<T: DeserializeOwned>(segment: &str) -> Result<T, JwtError> {
+	let json = decode_base64(segment)?;
+	serde_json::from_slice(&json).map_err(|e| JwtError::Malformed(format!("{:?}", e)))
+}
+
+pub(crate) fn encode_base64(bytes: impl AsRef<[u8]>) -> String {
+	base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+pub(crate) fn decode_base64(segment: &str) -> Result<Vec<u8>, JwtError> {
+	base64::decode_config(segment, base64::URL_SAFE_NO_PAD)
+		.map_err(|e| JwtError::Malformed(format!("{:?}", e)))
+}
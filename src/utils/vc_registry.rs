@@ -0,0 +1,132 @@
+use crate::{
+	primitives::{
+		assertion::Assertion,
+		vc::{Status, VCContext},
+		AccountId, CResult, VCIndex,
+	},
+	ParachainOp,
+};
+use codec::{Decode, Encode};
+use sp_core::H256;
+use std::collections::HashMap;
+
+/// `subject` (AccountId) is always the leading field of an encoded `VCContext`.
+const SUBJECT_LEN: usize = 32;
+/// `hash` (H256) + `status` (one byte enum) are always the trailing fields.
+const HASH_AND_STATUS_LEN: usize = 33;
+
+/// A `VCRegistry` entry which can not be decoded with the `VCContext` of this SDK, most of the
+/// time because the parachain knows an `Assertion` variant we don't.
+/// The fields that have a fixed position in the encoding are still extracted on a best effort
+/// basis, so that such entries can at least be filtered by subject.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndecodableVCContext {
+	pub subject: Option<AccountId>,
+	pub hash: Option<H256>,
+	pub status: Option<Status>,
+	pub raw: Vec<u8>,
+	pub error: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VCRegistry {
+	pub contexts: HashMap<VCIndex, VCContext>,
+	pub undecodable: HashMap<VCIndex, UndecodableVCContext>,
+}
+
+impl VCRegistry {
+	pub fn insert_encoded(&mut self, index: VCIndex, encoded: &[u8]) {
+		match decode_vc_context(encoded) {
+			Ok(context) => {
+				self.contexts.insert(index, context);
+			},
+			Err(undecodable) => {
+				self.undecodable.insert(index, undecodable);
+			},
+		}
+	}
+
+	pub fn by_subject(&self, subject: &AccountId) -> HashMap<VCIndex, VCContext> {
+		self.filter(|context| &context.subject == subject)
+	}
+
+	pub fn by_assertion(&self, assertion: &Assertion) -> HashMap<VCIndex, VCContext> {
+		self.filter(|context| &context.assertion == assertion)
+	}
+
+	pub fn undecodable_by_subject(
+		&self,
+		subject: &AccountId,
+	) -> HashMap<VCIndex, UndecodableVCContext> {
+		self.undecodable
+			.iter()
+			.filter(|(_, context)| context.subject.as_ref() == Some(subject))
+			.map(|(index, context)| (*index, context.clone()))
+			.collect()
+	}
+
+	pub fn filter<F>(&self, predicate: F) -> HashMap<VCIndex, VCContext>
+	where
+		F: Fn(&VCContext) -> bool,
+	{
+		self.contexts
+			.iter()
+			.filter(|(_, context)| predicate(context))
+			.map(|(index, context)| (*index, context.clone()))
+			.collect()
+	}
+}
+
+/// Where the verifier looks up the `VCContext` of a credential.
+pub trait VCRegistryLookup {
+	/// The SCALE encoded `VCContext`, see `decode_vc_context`.
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>>;
+}
+
+/// Live lookup of the latest state.
+impl<T: ParachainOp> VCRegistryLookup for T {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		ParachainOp::encoded_vc_context(self, *index)
+	}
+}
+
+impl VCRegistryLookup for VCRegistry {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		let encoded = match self.contexts.get(index) {
+			Some(context) => Some(context.encode()),
+			None => self.undecodable.get(index).map(|context| context.raw.clone()),
+		};
+
+		Ok(encoded)
+	}
+}
+
+/// `VCRegistry` is a `Blake2_128Concat` map, so the `VCIndex` is the trailing 32 bytes of the
+/// storage key.
+pub fn vc_index_from_storage_key(storage_key: &[u8]) -> Option<VCIndex> {
+	if storage_key.len() < 32 {
+		return None
+	}
+
+	Some(VCIndex::from_slice(&storage_key[storage_key.len() - 32..]))
+}
+
+/// Version tolerant `VCContext` decoder.
+/// Trailing bytes are ignored, so fields appended by newer runtimes don't break the decoding.
+pub fn decode_vc_context(encoded: &[u8]) -> Result<VCContext, UndecodableVCContext> {
+	VCContext::decode(&mut &encoded[..]).map_err(|e| {
+		let subject = encoded
+			.get(..SUBJECT_LEN)
+			.and_then(|mut subject| AccountId::decode(&mut subject).ok());
+
+		let (hash, status) = match encoded.len().checked_sub(HASH_AND_STATUS_LEN) {
+			Some(start) if start >= SUBJECT_LEN => {
+				let mut tail = &encoded[start..];
+				(H256::decode(&mut tail).ok(), Status::decode(&mut tail).ok())
+			},
+			_ => (None, None),
+		};
+
+		UndecodableVCContext { subject, hash, status, raw: encoded.to_vec(), error: e.to_string() }
+	})
+}
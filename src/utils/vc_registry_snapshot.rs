@@ -0,0 +1,251 @@
+use crate::{
+	primitives::{cerror::CError, vc::Status, AccountId, BlockAt, BlockHash, CResult, VCIndex},
+	utils::{
+		hex::hex_encode,
+		storage::call_index,
+		vc_registry::{decode_vc_context, vc_index_from_storage_key, VCRegistryLookup},
+	},
+	Creek, ParachainOp,
+};
+use codec::Encode;
+use frame_metadata::RuntimeMetadataPrefixed;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use std::{
+	collections::BTreeMap,
+	fs,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+/// Bumped whenever the JSON layout of `VCRegistrySnapshot` changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const VC_MANAGEMENT_PALLET_NAME: &str = "VCManagement";
+
+/// The `VCManagement::VCRegistry` as it was at `block_hash`.
+/// Entries are kept SCALE encoded (hex), so that a snapshot can be taken and restored even when the
+/// `VCContext` of this SDK is out of date with the parachain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VCRegistrySnapshot {
+	pub version: u32,
+	/// 0x prefixed hex
+	pub block_hash: String,
+	/// Unix time in seconds when the snapshot was taken.
+	pub timestamp: u64,
+	/// 0x prefixed hex `VCIndex` -> hex encoded `VCContext`
+	pub entries: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusChange {
+	pub index: VCIndex,
+	pub from: Option<Status>,
+	pub to: Option<Status>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssertionDiff {
+	pub added: Vec<VCIndex>,
+	pub removed: Vec<VCIndex>,
+	pub status_changed: Vec<StatusChange>,
+}
+
+/// Differences between two snapshots, keyed by the 0x prefixed hex of the SCALE encoded assertion.
+/// Entries that can't be decoded are grouped under `UNDECODABLE_ASSERTION`.
+pub type VCRegistryDiff = BTreeMap<String, AssertionDiff>;
+
+pub const UNDECODABLE_ASSERTION: &str = "Undecodable";
+
+/// `VCManagement::add_vc_registry_item` call data re-creating a missing entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestoreCall {
+	pub index: VCIndex,
+	/// 0x prefixed hex, the call has to be dispatched by the VCManagement admin.
+	pub call_data: String,
+}
+
+impl VCRegistrySnapshot {
+	pub fn take(creek: &Creek, at: BlockAt) -> CResult<Self> {
+		let block_hash = creek.parachain_client.block_hash(at)?;
+
+		let mut entries = BTreeMap::new();
+		for (key, context) in creek.vc_registry_at(BlockAt::Hash(block_hash))? {
+			let storage_key = hex::decode(&key[2..]).map_err(CError::FromHexError)?;
+			let vc_index = vc_index_from_storage_key(&storage_key)
+				.ok_or_else(|| CError::Other(format!("Invalid VCRegistry storage key: {}", key)))?;
+
+			entries.insert(hex_encode(vc_index.as_bytes()), context);
+		}
+
+		Ok(Self {
+			version: SNAPSHOT_VERSION,
+			block_hash: hex_encode(block_hash.as_bytes()),
+			timestamp: now(),
+			entries,
+		})
+	}
+
+	/// Writes the snapshot as `{dir}/{timestamp}-vcregistry.json`.
+	pub fn save(&self, dir: impl AsRef<Path>) -> CResult<PathBuf> {
+		let json = serde_json::to_string_pretty(self).map_err(CError::DecodeJsonError)?;
+		let path = dir.as_ref().join(format!("{}-vcregistry.json", self.timestamp));
+		fs::write(&path, json).map_err(|e| CError::Other(format!("{:?}", e)))?;
+
+		Ok(path)
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> CResult<Self> {
+		let json = fs::read(path).map_err(|e| CError::Other(format!("{:?}", e)))?;
+		let snapshot: Self = serde_json::from_slice(&json).map_err(CError::DecodeJsonError)?;
+		if snapshot.version != SNAPSHOT_VERSION {
+			return Err(CError::Other(format!(
+				"Unsupported VCRegistry snapshot version: {}",
+				snapshot.version
+			)))
+		}
+
+		Ok(snapshot)
+	}
+
+	pub fn block_hash(&self) -> CResult<BlockHash> {
+		parse_h256(&self.block_hash)
+	}
+
+	/// The decoded entries, see `decode_entry`.
+	pub fn decoded_entries(&self) -> CResult<BTreeMap<VCIndex, SnapshotEntry>> {
+		self.entries
+			.iter()
+			.map(|(index, context)| Ok((parse_h256(index)?, decode_entry(context)?)))
+			.collect()
+	}
+
+	/// What changed from `self` to `other`.
+	pub fn diff(&self, other: &Self) -> CResult<VCRegistryDiff> {
+		let before = self.decoded_entries()?;
+		let after = other.decoded_entries()?;
+
+		let mut diff = VCRegistryDiff::new();
+		for (index, entry) in &before {
+			match after.get(index) {
+				None => diff.entry(entry.assertion.clone()).or_default().removed.push(*index),
+				Some(new_entry) if new_entry.status != entry.status =>
+					diff.entry(entry.assertion.clone()).or_default().status_changed.push(
+						StatusChange {
+							index: *index,
+							from: entry.status.clone(),
+							to: new_entry.status.clone(),
+						},
+					),
+				Some(_) => {},
+			}
+		}
+		for (index, entry) in &after {
+			if !before.contains_key(index) {
+				diff.entry(entry.assertion.clone()).or_default().added.push(*index);
+			}
+		}
+
+		Ok(diff)
+	}
+
+	/// The call data restoring the entries of `self` which are missing from `current`.
+	/// Restored entries are always `Active`, entries that were `Disabled` have to be disabled
+	/// again by their subject.
+	pub fn restore_calls(
+		&self,
+		current: &Self,
+		metadata: &RuntimeMetadataPrefixed,
+	) -> CResult<Vec<RestoreCall>> {
+		let call_index = call_index(metadata, VC_MANAGEMENT_PALLET_NAME, "add_vc_registry_item")?;
+
+		let mut calls = vec![];
+		for (index, context) in &self.entries {
+			if current.entries.contains_key(index) {
+				continue
+			}
+
+			let index = parse_h256(index)?;
+			let args = restore_call_args(index, context)?;
+			let call_data = [&call_index[..], &args[..]].concat();
+
+			calls.push(RestoreCall { index, call_data: hex_encode(&call_data) });
+		}
+
+		Ok(calls)
+	}
+}
+
+impl VCRegistryLookup for VCRegistrySnapshot {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		self.entries
+			.get(&hex_encode(index.as_bytes()))
+			.map(|context| {
+				hex::decode(context.trim_start_matches("0x")).map_err(CError::FromHexError)
+			})
+			.transpose()
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotEntry {
+	pub subject: Option<AccountId>,
+	/// 0x prefixed hex of the SCALE encoded assertion, or `UNDECODABLE_ASSERTION`
+	pub assertion: String,
+	pub status: Option<Status>,
+}
+
+pub fn decode_entry(context: &str) -> CResult<SnapshotEntry> {
+	let encoded = hex::decode(context.trim_start_matches("0x")).map_err(CError::FromHexError)?;
+
+	let entry = match decode_vc_context(&encoded) {
+		Ok(context) => SnapshotEntry {
+			subject: Some(context.subject),
+			assertion: hex_encode(&context.assertion.encode()),
+			status: Some(context.status),
+		},
+		Err(context) => SnapshotEntry {
+			subject: context.subject,
+			assertion: UNDECODABLE_ASSERTION.to_string(),
+			status: context.status,
+		},
+	};
+
+	Ok(entry)
+}
+
+/// `(index, subject, assertion, hash)`
+/// An undecodable context is laid out as `subject ++ assertion ++ hash ++ status`, so its raw
+/// bytes without the trailing status are used as is.
+fn restore_call_args(index: VCIndex, context: &str) -> CResult<Vec<u8>> {
+	let encoded = hex::decode(context.trim_start_matches("0x")).map_err(CError::FromHexError)?;
+
+	let args = match decode_vc_context(&encoded) {
+		Ok(context) => (index, context.subject, context.assertion, context.hash).encode(),
+		Err(context) => {
+			if context.subject.is_none() || context.hash.is_none() {
+				return Err(CError::Other(format!("Can't restore VCRegistry entry {:?}", index)))
+			}
+			[&index.encode()[..], &encoded[..encoded.len() - 1]].concat()
+		},
+	};
+
+	Ok(args)
+}
+
+fn parse_h256(hex_str: &str) -> CResult<H256> {
+	let bytes = hex::decode(hex_str.trim_start_matches("0x")).map_err(CError::FromHexError)?;
+	if bytes.len() != 32 {
+		return Err(CError::Other(format!("Invalid H256: {}", hex_str)))
+	}
+
+	Ok(H256::from_slice(&bytes))
+}
+
+fn now() -> u64 {
+	SystemTime::now()
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.expect("Failed to retrieve timestamp")
+		.as_secs()
+}
@@ -0,0 +1,217 @@
+use crate::{
+	primitives::{
+		enclave::Enclave, identity::Identity, vc::Credential, AccountId, CResult, VCIndex,
+	},
+	utils::{
+		enclave_registry::EnclaveLookup,
+		vc::{
+			credential_hash, vc_schema, verify_vc_checks, verify_vc_proof, verify_vc_raw_checks,
+			TimePolicy, VerificationReport, VerifyContext,
+		},
+		vc_registry::VCRegistryLookup,
+	},
+};
+use jsonschema::JSONSchema;
+use sp_core::{ed25519, H256};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, PoisonError},
+	thread,
+};
+
+/// Verifies many credentials against the same `VerifyContext`, e.g. when re-verifying stored
+/// credentials. The credential schema is compiled once, and the enclave and `VCRegistry` lookups
+/// are cached for the lifetime of the verifier, so a verifier should not outlive the state it
+/// verifies against.
+pub struct Verifier<'a> {
+	vc_pubkey: ed25519::Public,
+	holder: Option<Identity>,
+	time: Option<TimePolicy>,
+	schema: JSONSchema,
+	enclaves: Option<CachedEnclaves<'a>>,
+	registry: Option<CachedRegistry<'a>>,
+}
+
+impl<'a> Verifier<'a> {
+	pub fn new(vc_pubkey: ed25519::Public) -> Result<Self, String> {
+		Ok(Self {
+			vc_pubkey,
+			holder: None,
+			time: None,
+			schema: vc_schema()?,
+			enclaves: None,
+			registry: None,
+		})
+	}
+
+	/// Every credential must be issued to `holder`, see `VerifyContext::with_holder`.
+	pub fn with_holder(mut self, holder: impl Into<Identity>) -> Self {
+		self.holder = Some(holder.into());
+		self
+	}
+
+	pub fn with_enclaves(mut self, enclaves: &'a (dyn EnclaveLookup + Sync)) -> Self {
+		self.enclaves = Some(CachedEnclaves { inner: enclaves, enclaves: Mutex::new(None) });
+		self
+	}
+
+	pub fn with_time(mut self, time: TimePolicy) -> Self {
+		self.time = Some(time);
+		self
+	}
+
+	pub fn with_registry(mut self, registry: &'a (dyn VCRegistryLookup + Sync)) -> Self {
+		self.registry = Some(CachedRegistry { inner: registry, contexts: Mutex::default() });
+		self
+	}
+
+	/// `vc` is verified as `Credential` serialises it, stored credentials need `verify_raw`.
+	pub fn verify(&self, vc: &Credential) -> VerificationReport {
+		let hashes: Vec<H256> = credential_hash(vc).into_iter().collect();
+
+		verify_vc_checks(&self.context(), vc, &hashes, Some(&self.schema), || {
+			verify_vc_proof(&self.vc_pubkey, vc)
+		})
+	}
+
+	/// Verifies the credential JSON as stored or returned by the worker, see `verify_vc_raw_with`
+	/// and `Credential`.
+	pub fn verify_raw(&self, json: &[u8]) -> Result<VerificationReport, String> {
+		verify_vc_raw_checks(&self.context(), json, Some(&self.schema))
+	}
+
+	/// Verifies `vcs` on all the available cores, the reports are in the order of `vcs`.
+	pub fn verify_batch(&self, vcs: &[Credential]) -> Vec<VerificationReport> {
+		self.verify_batch_with_threads(vcs, available_threads())
+	}
+
+	/// The credentials a panicking thread was verifying fail every check.
+	pub fn verify_batch_with_threads(
+		&self,
+		vcs: &[Credential],
+		threads: usize,
+	) -> Vec<VerificationReport> {
+		self.batch(vcs, threads, |vc| self.verify(vc), || VerificationReport::failed(PANICKED))
+	}
+
+	/// `verify_raw` of `jsons` on all the available cores, the reports are in the order of `jsons`.
+	pub fn verify_raw_batch<T: AsRef<[u8]> + Sync>(
+		&self,
+		jsons: &[T],
+	) -> Vec<Result<VerificationReport, String>> {
+		self.verify_raw_batch_with_threads(jsons, available_threads())
+	}
+
+	/// The credentials a panicking thread was verifying are an error.
+	pub fn verify_raw_batch_with_threads<T: AsRef<[u8]> + Sync>(
+		&self,
+		jsons: &[T],
+		threads: usize,
+	) -> Vec<Result<VerificationReport, String>> {
+		self.batch(
+			jsons,
+			threads,
+			|json| self.verify_raw(json.as_ref()),
+			|| Err(PANICKED.to_string()),
+		)
+	}
+
+	fn context(&self) -> VerifyContext<'_> {
+		VerifyContext {
+			vc_pubkey: self.vc_pubkey,
+			enclaves: self.enclaves.as_ref().map(|enclaves| enclaves as &dyn EnclaveLookup),
+			holder: self.holder.clone(),
+			time: self.time,
+			registry: self.registry.as_ref().map(|registry| registry as &dyn VCRegistryLookup),
+		}
+	}
+
+	/// `verify` of each item, on `threads` threads. A thread which panics doesn't abort the
+	/// batch, `panicked` stands for the result of each item it was verifying.
+	fn batch<T: Sync, R: Send>(
+		&self,
+		items: &[T],
+		threads: usize,
+		verify: impl Fn(&T) -> R + Sync,
+		panicked: impl Fn() -> R,
+	) -> Vec<R> {
+		if items.is_empty() {
+			return vec![]
+		}
+		let chunk_size = (items.len() + threads.max(1) - 1) / threads.max(1);
+
+		thread::scope(|scope| {
+			let handles: Vec<_> = items
+				.chunks(chunk_size)
+				.map(|chunk| {
+					let verify = &verify;
+					let handle = scope.spawn(move || chunk.iter().map(verify).collect::<Vec<_>>());
+					(chunk.len(), handle)
+				})
+				.collect();
+
+			handles
+				.into_iter()
+				.flat_map(|(len, handle)| {
+					handle.join().unwrap_or_else(|_| (0..len).map(|_| panicked()).collect())
+				})
+				.collect()
+		})
+	}
+}
+
+const PANICKED: &str = "verifier thread panicked";
+
+fn available_threads() -> usize {
+	thread::available_parallelism().map(usize::from).unwrap_or(1)
+}
+
+/// The registered enclaves are only fetched once, failed lookups are not cached.
+struct CachedEnclaves<'a> {
+	inner: &'a (dyn EnclaveLookup + Sync),
+	enclaves: Mutex<Option<Vec<Enclave<AccountId, String>>>>,
+}
+
+impl EnclaveLookup for CachedEnclaves<'_> {
+	fn enclaves(&self) -> CResult<Vec<Enclave<AccountId, String>>> {
+		let mut enclaves = self.enclaves.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(enclaves) = enclaves.as_ref() {
+			return Ok(enclaves.clone())
+		}
+
+		let fetched = self.inner.enclaves()?;
+		*enclaves = Some(fetched.clone());
+		Ok(fetched)
+	}
+}
+
+/// The lookup of one `VCIndex`, `None` until it succeeded.
+type ContextSlot = Arc<Mutex<Option<Option<Vec<u8>>>>>;
+
+/// Each `VCContext` is only looked up once, failed lookups are not cached.
+struct CachedRegistry<'a> {
+	inner: &'a (dyn VCRegistryLookup + Sync),
+	contexts: Mutex<HashMap<VCIndex, ContextSlot>>,
+}
+
+impl VCRegistryLookup for CachedRegistry<'_> {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		// Only the slot of `index` is locked during the lookup, so that the threads looking up
+		// other indexes don't wait, while the ones looking up the same index do.
+		let slot = self
+			.contexts
+			.lock()
+			.unwrap_or_else(PoisonError::into_inner)
+			.entry(*index)
+			.or_default()
+			.clone();
+		let mut context = slot.lock().unwrap_or_else(PoisonError::into_inner);
+		if let Some(context) = context.as_ref() {
+			return Ok(context.clone())
+		}
+
+		let fetched = self.inner.lookup_vc_context(index)?;
+		*context = Some(fetched.clone());
+		Ok(fetched)
+	}
+}
@@ -1,4 +1,32 @@
-use creek::{primitives::vc::VCContext, utils::hex::FromHexPrefixed};
+use codec::{Decode, Encode};
+use creek::{
+	primitives::{
+		address::Address32,
+		assertion::Assertion,
+		error::{ErrorDetail, VCMPError},
+		event::{ParachainEvent, VCManagementEvent},
+		vc::{Status, VCContext},
+		AccountId, VCIndex,
+	},
+	service::impls::delegatee::delegatee_from_storage_key,
+	utils::{
+		address::vec_to_u8_array,
+		hex::{hex_encode, FromHexPrefixed},
+		storage::{call_index, storage_entry},
+		vc_registry::{decode_vc_context, vc_index_from_storage_key, VCRegistry},
+		vc_registry_snapshot::{VCRegistrySnapshot, SNAPSHOT_VERSION},
+	},
+};
+use frame_metadata::{
+	v14::{
+		ExtrinsicMetadata, PalletCallMetadata, PalletMetadata, PalletStorageMetadata,
+		RuntimeMetadataV14, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+		StorageHasher,
+	},
+	RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED,
+};
+use scale_info::{meta_type, TypeInfo};
+use sp_core::H256;
 
 #[test]
 fn decode_vc_context_works() {
@@ -20,3 +48,241 @@ fn decode_vc_context_works() {
 	// 	status: Active,
 	// }
 }
+
+#[test]
+fn decode_vc_context_tolerates_trailing_bytes() {
+	let encoded = hex::decode("645d79a63bd3b0b47ceba0a4c9824d7d8e7a1627c67f11765c3a70ad691e14f706040779df561d21d15e8a3fcc984eb7ca5987a3cfe2f92ca9920a4fcdb9b3eea625f20001").unwrap();
+	let context = decode_vc_context(&encoded).unwrap();
+	assert_eq!(context.status, Status::Active);
+}
+
+#[test]
+fn undecodable_vc_context_keeps_subject() {
+	// Same context as above, but with an `Assertion` index this SDK doesn't know.
+	let encoded = hex::decode("645d79a63bd3b0b47ceba0a4c9824d7d8e7a1627c67f11765c3a70ad691e14f7ff040779df561d21d15e8a3fcc984eb7ca5987a3cfe2f92ca9920a4fcdb9b3eea625f200").unwrap();
+	let index = VCIndex::repeat_byte(1);
+
+	let mut registry = VCRegistry::default();
+	registry.insert_encoded(index, &encoded);
+	assert!(registry.contexts.is_empty());
+
+	let undecodable = registry.undecodable.get(&index).unwrap();
+	let subject = AccountId::from(vec_to_u8_array::<32>(encoded[..32].to_vec()));
+	assert_eq!(undecodable.subject, Some(subject.clone()));
+	assert_eq!(undecodable.status, Some(Status::Active));
+	assert_eq!(registry.undecodable_by_subject(&subject).len(), 1);
+}
+
+#[test]
+fn vc_index_from_storage_key_works() {
+	let index = VCIndex::repeat_byte(7);
+	let mut storage_key =
+		hex::decode("b8806b89e4f9af656f87b35e6112ee1bda2e7b4c5a367debe17c26748ec6b3e6").unwrap();
+	storage_key.extend_from_slice(&[0u8; 16]);
+	storage_key.extend_from_slice(index.as_bytes());
+
+	assert_eq!(vc_index_from_storage_key(&storage_key), Some(index));
+	assert_eq!(vc_index_from_storage_key(&[0u8; 16]), None);
+}
+
+#[test]
+fn decode_vc_management_events_works() {
+	let account = AccountId::new([1u8; 32]);
+	let index = VCIndex::repeat_byte(2);
+	let req_ext_hash = VCIndex::repeat_byte(3);
+
+	let fields = (account.clone(), Assertion::A1, index, req_ext_hash).encode();
+	let event = ParachainEvent::decode("VCManagement", "VCIssued", &fields).unwrap();
+	assert_eq!(
+		event,
+		Some(ParachainEvent::VCManagement(VCManagementEvent::VCIssued {
+			account: account.clone(),
+			assertion: Assertion::A1,
+			index,
+			req_ext_hash,
+		}))
+	);
+
+	let fields =
+		(Some(account.clone()), Assertion::A1, ErrorDetail::ParseError, req_ext_hash).encode();
+	let event = ParachainEvent::decode("VCManagement", "RequestVCFailed", &fields).unwrap();
+	assert_eq!(
+		event,
+		Some(ParachainEvent::VCManagement(VCManagementEvent::Failed {
+			account: Some(account),
+			error: VCMPError::RequestVCFailed(Assertion::A1, ErrorDetail::ParseError),
+			req_ext_hash,
+		}))
+	);
+
+	assert_eq!(ParachainEvent::decode("Balances", "Transfer", &[]).unwrap(), None);
+	assert!(ParachainEvent::decode("VCManagement", "VCIssued", &[0u8; 4]).is_err());
+}
+
+#[test]
+fn vc_registry_snapshot_diff_works() {
+	let subject = AccountId::new([1u8; 32]);
+	let active = VCContext::new(subject.clone(), Assertion::A1, VCIndex::repeat_byte(9));
+	let mut disabled = active.clone();
+	disabled.status = Status::Disabled;
+
+	let snapshot = |entries: Vec<(u8, &VCContext)>| VCRegistrySnapshot {
+		version: SNAPSHOT_VERSION,
+		block_hash: format!("0x{}", "00".repeat(32)),
+		timestamp: 0,
+		entries: entries
+			.into_iter()
+			.map(|(index, context)| {
+				(format!("0x{}", hex::encode([index; 32])), hex::encode(context.encode()))
+			})
+			.collect(),
+	};
+
+	let before = snapshot(vec![(1, &active), (2, &active)]);
+	let after = snapshot(vec![(2, &disabled), (3, &active)]);
+
+	let diff = before.diff(&after).unwrap();
+	let a1 = diff.get(&hex_encode(&Assertion::A1.encode())).unwrap();
+	assert_eq!(a1.removed, vec![VCIndex::repeat_byte(1)]);
+	assert_eq!(a1.added, vec![VCIndex::repeat_byte(3)]);
+	assert_eq!(a1.status_changed.len(), 1);
+	assert_eq!(a1.status_changed[0].index, VCIndex::repeat_byte(2));
+	assert_eq!(a1.status_changed[0].to, Some(Status::Disabled));
+}
+
+/// The `IdentityManagement` calls `DelegateeOp` submits, with made up indexes.
+#[allow(dead_code, non_camel_case_types)]
+#[derive(Encode, TypeInfo)]
+enum IdentityManagementCall {
+	#[codec(index = 0)]
+	add_delegatee { account: AccountId },
+	#[codec(index = 1)]
+	remove_delegatee { account: AccountId },
+}
+
+/// Only the index of the call is looked up, its arguments are left out as `Assertion` has no
+/// `TypeInfo`.
+#[allow(dead_code, non_camel_case_types)]
+#[derive(Encode, TypeInfo)]
+enum VCManagementCall {
+	#[codec(index = 2)]
+	add_vc_registry_item,
+}
+
+/// Runtime metadata with only the `IdentityManagement` (64) and `VCManagement` (65) pallets.
+fn runtime_metadata() -> RuntimeMetadataPrefixed {
+	let identity_management = PalletMetadata {
+		name: "IdentityManagement",
+		storage: Some(PalletStorageMetadata {
+			prefix: "IdentityManagement",
+			entries: vec![StorageEntryMetadata {
+				name: "Delegatee",
+				modifier: StorageEntryModifier::Optional,
+				ty: StorageEntryType::Map {
+					hashers: vec![StorageHasher::Blake2_128Concat],
+					key: meta_type::<AccountId>(),
+					value: meta_type::<()>(),
+				},
+				default: vec![0],
+				docs: vec![],
+			}],
+		}),
+		calls: Some(PalletCallMetadata { ty: meta_type::<IdentityManagementCall>() }),
+		event: None,
+		constants: vec![],
+		error: None,
+		index: 64,
+	};
+	let vc_management = PalletMetadata {
+		name: "VCManagement",
+		storage: None,
+		calls: Some(PalletCallMetadata { ty: meta_type::<VCManagementCall>() }),
+		event: None,
+		constants: vec![],
+		error: None,
+		index: 65,
+	};
+	let extrinsic =
+		ExtrinsicMetadata { ty: meta_type::<()>(), version: 4, signed_extensions: vec![] };
+	let metadata = RuntimeMetadataV14::new(
+		vec![identity_management, vc_management],
+		extrinsic,
+		meta_type::<()>(),
+	);
+
+	RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V14(metadata))
+}
+
+#[test]
+fn delegatee_storage_key_works() {
+	let metadata = runtime_metadata();
+	let account = AccountId::new([5u8; 32]);
+
+	let entry = storage_entry(&metadata, "IdentityManagement", "Delegatee").unwrap();
+	let key = entry.map_key(&[Address32::from(account.clone()).encode()]).unwrap();
+	assert_eq!(key.len(), 32 + 16 + 32);
+	assert_eq!(&key[..32], entry.prefix().as_slice());
+	assert_eq!(delegatee_from_storage_key(&key), Some(account));
+	assert_eq!(delegatee_from_storage_key(&key[..16]), None);
+}
+
+#[test]
+fn delegatee_call_encoding_works() {
+	let metadata = runtime_metadata();
+	let account = AccountId::new([5u8; 32]);
+
+	let add = call_index(&metadata, "IdentityManagement", "add_delegatee").unwrap();
+	let remove = call_index(&metadata, "IdentityManagement", "remove_delegatee").unwrap();
+	assert_eq!(add, [64, 0]);
+	assert_eq!(remove, [64, 1]);
+	assert!(call_index(&metadata, "IdentityManagement", "set_delegatee").is_err());
+
+	// `submit_call` encodes the call as its index followed by the arguments.
+	let mut expected = vec![64, 0];
+	expected.extend_from_slice(account.as_ref());
+	assert_eq!((add, account.clone()).encode(), expected);
+	assert_eq!(
+		(add, account.clone()).encode(),
+		IdentityManagementCall::add_delegatee { account }.encode()
+	);
+}
+
+#[test]
+fn vc_registry_snapshot_restore_calls_works() {
+	let context = VCContext::new(AccountId::new([1u8; 32]), Assertion::A1, H256::repeat_byte(9));
+	// An `Assertion` index this SDK doesn't know, see `undecodable_vc_context_keeps_subject`
+	let undecodable = "645d79a63bd3b0b47ceba0a4c9824d7d8e7a1627c67f11765c3a70ad691e14f7ff040779df561d21d15e8a3fcc984eb7ca5987a3cfe2f92ca9920a4fcdb9b3eea625f200";
+
+	let snapshot = |entries: Vec<(u8, String)>| VCRegistrySnapshot {
+		version: SNAPSHOT_VERSION,
+		block_hash: format!("0x{}", "00".repeat(32)),
+		timestamp: 0,
+		entries: entries
+			.into_iter()
+			.map(|(index, context)| (format!("0x{}", hex::encode([index; 32])), context))
+			.collect(),
+	};
+	let before = snapshot(vec![
+		(1, hex::encode(context.encode())),
+		(2, hex::encode(context.encode())),
+		(3, undecodable.to_string()),
+	]);
+	let current = snapshot(vec![(2, hex::encode(context.encode()))]);
+
+	let calls = before.restore_calls(&current, &runtime_metadata()).unwrap();
+	assert_eq!(calls.len(), 2);
+
+	assert_eq!(calls[0].index, VCIndex::repeat_byte(1));
+	let call_data = hex::decode(calls[0].call_data.trim_start_matches("0x")).unwrap();
+	assert_eq!(call_data[..2], [65, 2]);
+	let (index, subject, assertion, hash) =
+		<(VCIndex, AccountId, Assertion, H256)>::decode(&mut &call_data[2..]).unwrap();
+	assert_eq!(index, VCIndex::repeat_byte(1));
+	assert_eq!((subject, assertion, hash), (context.subject, context.assertion, context.hash));
+
+	// The undecodable context is passed on as is, without its status.
+	assert_eq!(calls[1].index, VCIndex::repeat_byte(3));
+	let call_data = hex::decode(calls[1].call_data.trim_start_matches("0x")).unwrap();
+	assert_eq!(call_data[2..34], [3u8; 32]);
+	assert_eq!(hex::encode(&call_data[34..]), undecodable[..undecodable.len() - 2]);
+}
@@ -0,0 +1,1189 @@
+use chrono::NaiveDate;
+use codec::{Decode, Encode};
+use creek::{
+	primitives::{
+		address::{Address20, Address33},
+		assertion::{
+			AchainableBasic, AchainableParams, Assertion, EVMTokenType, VIP3MembershipCardLevel,
+		},
+		enclave::Enclave,
+		identity::Identity,
+		keypair::KeyPair,
+		network::Web3Network,
+		presentation::Presentation,
+		vc::{AssertionLogic, Credential, CredentialV2, Op, Status, VCContext, VCSchema},
+		AccountId, CResult, VCIndex,
+	},
+	utils::{
+		assertion_logic::{check_values, evaluate, Bindings, EvalError, Value},
+		assertion_schema::{validate_vc_schema, validate_vc_schema_for, ASSERTION_SCHEMAS},
+		data_integrity::{
+			add_data_integrity_proof, sign_vc_data_integrity, verify_data_integrity, Cryptosuite,
+			DataIntegrityError,
+		},
+		enclave_registry::{EnclaveLookup, EnclaveRegistrySnapshot},
+		hex::hex_encode,
+		json_ld::to_rdf,
+		presentation::{
+			verify_presentation, verify_presentation_holder, verify_presentation_raw,
+			PresentationError,
+		},
+		public_api::mrenclave_to_bs58,
+		rdfc,
+		sd_jwt::{verify_sd_jwt, verify_sd_jwt_with, SdJwt},
+		vc::{
+			credential_hash, vc_schema, verify_vc_issuer, verify_vc_proof, verify_vc_proof_raw,
+			verify_vc_registered_schema, verify_vc_schema, verify_vc_status, verify_vc_subject,
+			verify_vc_time, verify_vc_with, verify_versioned_vc_with, Check, CheckOutcome,
+			CredentialStatus, StatusError, SubjectError, TimeError, TimePolicy, VerificationReport,
+			VerifyContext, VersionedCredential,
+		},
+		vc_builder::CredentialBuilder,
+		vc_jwt::{decode_vc_jwt, encode_vc_jwt, verify_vc_jwt, JwtError},
+		vc_registry::{VCRegistry, VCRegistryLookup},
+		verifier::Verifier,
+	},
+};
+use serde_json::json;
+use sp_core::{ed25519, sr25519, Pair};
+use std::{
+	collections::HashMap,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	},
+};
+
+const ENCLAVE_ACCOUNT: [u8; 32] = [0x43; 32];
+const MRENCLAVE: [u8; 32] = [0x7a; 32];
+const SUBJECT: &str = "d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+fn credential() -> Credential {
+	serde_json::from_value(json!({
+		"@context": [
+			"https://www.w3.org/2018/credentials/v1",
+			"https://w3id.org/security/suites/ed25519-2020/v1"
+		],
+		"id": "0x9cb4a7753e750819b0dd34946e3145bdd3d8d7cee718b463e8959e41359ec716",
+		"type": ["VerifiableCredential"],
+		"credentialSubject": {
+			"id": SUBJECT,
+			"description": "The user has verified one identity in Web 2 and one identity in Web 3",
+			"type": "Basic Identity Verification",
+			"tag": ["Litentry Network"],
+			"assertions": [{
+				"and": [
+					{ "src": "$has_web2_account", "op": "==", "dst": "true" },
+					{ "src": "$has_web3_account", "op": "==", "dst": "true" }
+				]
+			}],
+			"values": [true],
+			"endpoint": "wss://rpc.litentry-parachain.litentry.io"
+		},
+		"issuer": {
+			"id": hex::encode(ENCLAVE_ACCOUNT),
+			"name": "Litentry TEE Worker",
+			"mrenclave": mrenclave_to_bs58(&MRENCLAVE)
+		},
+		"issuanceTimestamp": 1696663438000u64,
+		"proof": {
+			"createdTimestamp": 1696663438000u64,
+			"type": "Ed25519Signature2020",
+			"proofPurpose": "assertionMethod",
+			"proofValue": "00",
+			"verificationMethod": hex::encode(ENCLAVE_ACCOUNT)
+		}
+	}))
+	.unwrap()
+}
+
+/// `credential()` signed by `pair`, the way the worker signs it.
+fn signed_credential(pair: &ed25519::Pair) -> Credential {
+	let mut vc = credential();
+	vc.proof = None;
+	let signature = pair.sign(serde_json::to_string(&vc).unwrap().as_bytes());
+	vc.proof = credential().proof.map(|mut proof| {
+		proof.proof_value = hex::encode(signature.0);
+		proof.verification_method = hex::encode(pair.public().0);
+		proof
+	});
+	vc
+}
+
+fn enclave(mr_enclave: [u8; 32]) -> Enclave<AccountId, String> {
+	Enclave {
+		pubkey: AccountId::new(ENCLAVE_ACCOUNT),
+		mr_enclave,
+		timestamp: 0,
+		url: "wss://localhost:2000".to_string(),
+		shielding_key: None,
+		vc_pubkey: Some(ENCLAVE_ACCOUNT.to_vec()),
+		sgx_mode: Default::default(),
+		sgx_metadata: Default::default(),
+	}
+}
+
+#[test]
+fn verify_vc_issuer_works() {
+	let vc = credential();
+
+	let snapshot = EnclaveRegistrySnapshot::new(vec![], &[enclave(MRENCLAVE)]);
+	assert!(verify_vc_issuer(&vc, &snapshot).unwrap());
+
+	let snapshot = EnclaveRegistrySnapshot::new(vec![], &[enclave([0u8; 32])]);
+	assert!(!verify_vc_issuer(&vc, &snapshot).unwrap());
+
+	let mut vc = credential();
+	vc.issuer.id = hex::encode([0u8; 32]);
+	let snapshot = EnclaveRegistrySnapshot::new(vec![], &[enclave(MRENCLAVE)]);
+	assert!(!verify_vc_issuer(&vc, &snapshot).unwrap());
+
+	// signed with a key which isn't the `vc_pubkey` of the enclave
+	let mut vc = credential();
+	vc.proof.as_mut().unwrap().verification_method = hex::encode([0x44; 32]);
+	assert!(!verify_vc_issuer(&vc, &snapshot).unwrap());
+
+	let mut vc = credential();
+	vc.issuer.mrenclave = "1".to_string();
+	assert!(!verify_vc_issuer(&vc, &snapshot).unwrap());
+	vc.issuer.mrenclave = mrenclave_to_bs58(&MRENCLAVE) + "1";
+	assert!(!verify_vc_issuer(&vc, &snapshot).unwrap());
+}
+
+#[test]
+fn merged_enclave_snapshot_keeps_removed_enclaves() {
+	let mut snapshot = EnclaveRegistrySnapshot::new(vec![], &[enclave([0u8; 32])]);
+	snapshot.merge(&EnclaveRegistrySnapshot::new(vec![], &[enclave(MRENCLAVE)]));
+	snapshot.merge(&EnclaveRegistrySnapshot::new(vec![], &[enclave(MRENCLAVE)]));
+
+	assert_eq!(snapshot.enclaves.len(), 2);
+	assert!(verify_vc_issuer(&credential(), &snapshot).unwrap());
+}
+
+#[test]
+fn verify_vc_subject_works() {
+	let holder = AccountId::new(hex::decode(SUBJECT).unwrap().try_into().unwrap());
+	let vc = credential();
+	assert_eq!(verify_vc_subject(&vc, None), Ok(()));
+	assert_eq!(verify_vc_subject(&vc, Some(&Identity::from(holder.clone()))), Ok(()));
+
+	let mut vc = credential();
+	vc.credential_subject.id = Identity::from(holder.clone()).to_did().unwrap();
+	assert_eq!(verify_vc_subject(&vc, Some(&Identity::from(holder))), Ok(()));
+
+	let mut vc = credential();
+	vc.credential_subject.values.push(false);
+	vc.credential_subject.endpoint = "localhost".to_string();
+	let errors =
+		verify_vc_subject(&vc, Some(&Identity::Github("litentry".to_string()))).unwrap_err();
+	assert_eq!(errors.len(), 3);
+	assert!(matches!(errors[0], SubjectError::HolderMismatch { .. }));
+	assert_eq!(errors[1], SubjectError::ValuesLengthMismatch { assertions: 1, values: 2 });
+	assert_eq!(errors[2], SubjectError::InvalidEndpoint("localhost".to_string()));
+}
+
+#[test]
+fn verify_vc_time_works() {
+	let issued_at = 1696663438000u64;
+	let day = 24 * 60 * 60 * 1000;
+	let vc = credential();
+
+	assert_eq!(verify_vc_time(&vc, &TimePolicy::at(issued_at + day)), Ok(()));
+	// within the default clock skew
+	assert_eq!(verify_vc_time(&vc, &TimePolicy::at(issued_at - 1000)), Ok(()));
+	assert_eq!(
+		verify_vc_time(&vc, &TimePolicy::at(issued_at - day)),
+		Err(vec![
+			TimeError::NotYetValid { issued_at },
+			TimeError::ProofCreatedInFuture { created_at: issued_at }
+		])
+	);
+	assert_eq!(
+		verify_vc_time(&vc, &TimePolicy::at(issued_at + 31 * day).with_max_age(30 * day)),
+		Err(vec![TimeError::TooOld { issued_at }])
+	);
+
+	let mut vc = credential();
+	vc.expiration_timestamp = Some(issued_at + day);
+	assert_eq!(
+		verify_vc_time(&vc, &TimePolicy::at(issued_at + 2 * day).with_max_clock_skew(0)),
+		Err(vec![TimeError::Expired { expired_at: issued_at + day }])
+	);
+}
+
+#[test]
+fn evaluate_assertion_logic_works() {
+	let logic = serde_json::from_value(json!({
+		"and": [
+			{ "src": "$minimum_amount", "op": "==", "dst": "1.001" },
+			{ "src": "$from_date", "op": "<", "dst": "2017-01-01" },
+			{ "or": [
+				{ "src": "$network", "op": "==", "dst": "Litentry" },
+				{ "src": "$network", "op": "==", "dst": "Litmus" }
+			]}
+		]
+	}))
+	.unwrap();
+
+	let mut bindings = Bindings::new();
+	bindings.insert("$minimum_amount".to_string(), Value::from(1.001));
+	bindings.insert(
+		"$from_date".to_string(),
+		Value::from(NaiveDate::from_ymd_opt(2016, 12, 31).unwrap()),
+	);
+	bindings.insert("$network".to_string(), Value::from("Litmus"));
+	assert_eq!(evaluate(&logic, &bindings), Ok(true));
+
+	bindings.insert("$from_date".to_string(), Value::from(true));
+	bindings.remove("$network");
+	assert_eq!(
+		evaluate(&logic, &bindings),
+		Err(vec![
+			EvalError::TypeMismatch {
+				src: "$from_date".to_string(),
+				expected: "bool",
+				dst: "2017-01-01".to_string()
+			},
+			EvalError::UnknownVariable("$network".to_string()),
+			EvalError::UnknownVariable("$network".to_string()),
+		])
+	);
+}
+
+#[test]
+fn check_values_works() {
+	let vc = credential();
+	let mut bindings = Bindings::new();
+	bindings.insert("$has_web2_account".to_string(), Value::from(true));
+	bindings.insert("$has_web3_account".to_string(), Value::from(true));
+
+	let checks = check_values(&vc.credential_subject, &bindings);
+	assert_eq!(checks.len(), 1);
+	assert!(checks[0].is_consistent());
+
+	bindings.insert("$has_web3_account".to_string(), Value::from(false));
+	let checks = check_values(&vc.credential_subject, &bindings);
+	assert_eq!(checks[0].evaluated, Ok(false));
+	assert!(!checks[0].is_consistent());
+}
+
+#[test]
+fn verify_vc_schema_works() {
+	// The common schema requires the `issuanceTimestamp` credentials have, not the
+	// `issuanceBlockNumber` of the earlier layout which every credential failed.
+	assert_eq!(verify_vc_schema(&credential()), Ok(true));
+
+	let schema = vc_schema().unwrap();
+	let mut json = serde_json::to_value(credential()).unwrap();
+	json["issuanceBlockNumber"] = json!(1);
+	json.as_object_mut().unwrap().remove("issuanceTimestamp");
+	assert!(!schema.is_valid(&json));
+}
+
+#[test]
+fn validate_vc_schema_works() {
+	let vc = credential();
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+	assert_eq!(validate_vc_schema_for(&vc, &Assertion::A1), Ok(()));
+
+	let errors = validate_vc_schema_for(&vc, &Assertion::A6).unwrap_err();
+	assert!(errors.iter().all(|e| e.schema == "a6"));
+	assert!(errors.iter().any(|e| e.instance_path == "/credentialSubject/type"));
+
+	let mut vc = credential();
+	vc.credential_subject.description = "Something else".to_string();
+	let errors = validate_vc_schema(&vc).unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].instance_path, "/credentialSubject/description");
+
+	let mut vc = credential();
+	vc.credential_subject.tag = vec!["Twitter".to_string()];
+	let errors = validate_vc_schema(&vc).unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].instance_path, "/credentialSubject/tag");
+
+	// Every assertion has a schema, a type none of them lists is rejected.
+	let mut vc = credential();
+	vc.credential_subject.types = "Basic Identity Verificaton".to_string();
+	let errors = validate_vc_schema(&vc).unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].instance_path, "/credentialSubject/type");
+
+	// Parameterised assertions pair each type with its description.
+	let mut vc = credential();
+	vc.credential_subject.types = "VIP3 Gold Card Holder".to_string();
+	vc.credential_subject.description =
+		"The user is a holder of a VIP3 Gold membership card".to_string();
+	vc.credential_subject.tag = vec!["BSC".to_string()];
+	vc.credential_subject.assertions = vec![AssertionLogic::And {
+		items: vec![Box::new(item("$is_gold_card", Op::Equal, "true"))],
+	}];
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+	let vip3 = Assertion::VIP3MembershipCard(VIP3MembershipCardLevel::Gold);
+	assert_eq!(validate_vc_schema_for(&vc, &vip3), Ok(()));
+	vc.credential_subject.description =
+		"The user is a holder of a VIP3 Silver membership card".to_string();
+	assert!(validate_vc_schema(&vc).is_err());
+
+	// The assertion logic only uses the sources of the assertion.
+	let mut vc = credential();
+	vc.credential_subject.assertions = vec![AssertionLogic::And {
+		items: vec![Box::new(item("$total_followers", Op::Equal, "1"))],
+	}];
+	let errors = validate_vc_schema(&vc).unwrap_err();
+	assert!(errors.iter().all(|e| e.schema == "a1"));
+
+	// `EVMAmountHolding` and `BRC20AmountHolder` issue the same type, either schema may match.
+	let mut vc = credential();
+	vc.credential_subject.types = "Token Holding Amount".to_string();
+	vc.credential_subject.description =
+		"The amount of a particular token you are holding".to_string();
+	vc.credential_subject.tag = vec!["BRC20".to_string()];
+	vc.credential_subject.assertions =
+		vec![AssertionLogic::And { items: vec![Box::new(item("$token", Op::Equal, "ordi"))] }];
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+	assert_eq!(validate_vc_schema_for(&vc, &Assertion::BRC20AmountHolder), Ok(()));
+	assert!(validate_vc_schema_for(&vc, &Assertion::EVMAmountHolding(EVMTokenType::Ton)).is_err());
+
+	// Achainable types aren't listed, their credentials are checked with the assertion.
+	let mut vc = credential();
+	vc.credential_subject.types = "Uniswap V2/V3 User".to_string();
+	vc.credential_subject.description = "The user is an Uniswap V2 or V3 user".to_string();
+	vc.credential_subject.tag = vec!["Ethereum".to_string()];
+	vc.credential_subject.assertions = vec![AssertionLogic::And {
+		items: vec![Box::new(item("$is_uniswap_user", Op::Equal, "true"))],
+	}];
+	assert!(validate_vc_schema(&vc).is_err());
+	let achainable = Assertion::Achainable(AchainableParams::Basic(AchainableBasic {
+		name: "Uniswap V2/V3 user".to_string(),
+		chain: Web3Network::Ethereum,
+	}));
+	assert_eq!(validate_vc_schema_for(&vc, &achainable), Ok(()));
+	vc.credential_subject.tag = vec!["Uniswap".to_string()];
+	assert!(validate_vc_schema_for(&vc, &achainable).is_err());
+}
+
+#[test]
+fn assertion_schemas_compile() {
+	// A schema which doesn't compile lists no type.
+	for schema in ASSERTION_SCHEMAS.iter() {
+		assert_eq!(
+			schema.credential_types().is_empty(),
+			schema.name == "achainable",
+			"{}",
+			schema.name
+		);
+	}
+}
+
+#[test]
+fn verify_vc_status_works() {
+	let vc = credential();
+	let index = VCIndex::from_slice(&hex::decode(&vc.id[2..]).unwrap());
+	let subject = AccountId::new(hex::decode(SUBJECT).unwrap().try_into().unwrap());
+	let hash = credential_hash(&vc).unwrap();
+
+	let mut registry = VCRegistry::default();
+	assert_eq!(verify_vc_status(&vc, &registry), Ok(CredentialStatus::Unknown));
+
+	let mut context = VCContext::new(subject, Assertion::A1, hash);
+	registry.contexts.insert(index, context.clone());
+	assert_eq!(verify_vc_status(&vc, &registry), Ok(CredentialStatus::Active));
+
+	context.status = Status::Disabled;
+	registry.contexts.insert(index, context.clone());
+	assert_eq!(verify_vc_status(&vc, &registry), Ok(CredentialStatus::Disabled));
+
+	context.hash = VCIndex::zero();
+	registry.contexts.insert(index, context);
+	assert_eq!(
+		verify_vc_status(&vc, &registry),
+		Err(StatusError::HashMismatch { registered: VCIndex::zero(), computed: hash })
+	);
+
+	// EVM and Bitcoin subjects are registered with the account they are mapped to.
+	let subjects = [
+		Identity::Evm(Address20::from([0x11; 20])),
+		Identity::Bitcoin(Address33::from([0x02; 33])),
+	];
+	for subject in subjects {
+		let mut vc = credential();
+		vc.credential_subject.id = match &subject {
+			Identity::Evm(address) => hex::encode(address.as_ref()),
+			Identity::Bitcoin(address) => hex::encode(address.as_ref()),
+			_ => unreachable!(),
+		};
+		let hash = credential_hash(&vc).unwrap();
+		let registry = |subject: &AccountId| {
+			let context = VCContext::new(subject.clone(), Assertion::A1, hash);
+			let mut registry = VCRegistry::default();
+			registry.contexts.insert(index, context);
+			registry
+		};
+
+		let account = subject.to_account_id().unwrap();
+		assert_eq!(verify_vc_status(&vc, &registry(&account)), Ok(CredentialStatus::Active));
+
+		let raw = AccountId::new([0x11; 32]);
+		let subject = vc.credential_subject.id.clone();
+		assert_eq!(
+			verify_vc_status(&vc, &registry(&raw)),
+			Err(StatusError::SubjectMismatch { registered: raw, subject })
+		);
+	}
+}
+
+#[test]
+fn verify_presentation_works() {
+	let enclave_pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let vc = signed_credential(&enclave_pair);
+	let ctx = VerifyContext::new(enclave_pair.public());
+
+	let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+	let vp = Presentation::sign(&alice.clone().into(), vec![vc.clone()], "nonce", "example.com", 0)
+		.unwrap();
+	assert_eq!(verify_presentation(&ctx, &vp, "nonce", "example.com"), Ok(()));
+
+	let errors = verify_presentation(&ctx, &vp, "other nonce", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::ChallengeMismatch { .. }]));
+
+	let mut tampered = vp.clone();
+	tampered.proof.as_mut().unwrap().domain = "attacker.com".to_string();
+	let errors = verify_presentation(&ctx, &tampered, "nonce", "attacker.com").unwrap_err();
+	assert_eq!(errors, vec![PresentationError::InvalidSignature]);
+
+	// The holder signs the canonical JSON, the member order of the presented one doesn't matter.
+	let reordered = reversed_json(&serde_json::to_value(&vp).unwrap());
+	assert_ne!(reordered, serde_json::to_string(&vp).unwrap());
+	let reordered: Presentation = serde_json::from_str(&reordered).unwrap();
+	assert_eq!(verify_presentation(&ctx, &reordered, "nonce", "example.com"), Ok(()));
+
+	// A credential of another enclave fails `ctx`, the holder checks leave it to the caller.
+	let other = signed_credential(&ed25519::Pair::from_seed(&[0x24; 32]));
+	let vp = Presentation::sign(&alice.into(), vec![vc.clone(), other], "nonce", "example.com", 0)
+		.unwrap();
+	let errors = verify_presentation(&ctx, &vp, "nonce", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::InvalidCredential { index: 1, .. }]));
+	assert_eq!(verify_presentation_holder(&vp, "nonce", "example.com"), Ok(()));
+
+	let bob = sr25519::Pair::from_string("//Bob", None).unwrap();
+	let vp = Presentation::sign(&bob.into(), vec![vc], "nonce", "example.com", 0).unwrap();
+	let errors = verify_presentation(&ctx, &vp, "nonce", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::CredentialNotHeld { index: 0, .. }]));
+}
+
+#[test]
+fn verify_presentation_raw_works() {
+	let enclave_pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let ctx = VerifyContext::new(enclave_pair.public());
+	let signed = signed_json_with_unknown_member(&enclave_pair);
+	let vc: Credential = serde_json::from_str(&signed).unwrap();
+
+	// The holder presents the credential JSON as the worker returned it.
+	let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+	let vp =
+		Presentation::sign(&alice.into(), vec![vc.clone()], "nonce", "example.com", 0).unwrap();
+	let json = serde_json::to_string(&vp)
+		.unwrap()
+		.replace(&serde_json::to_string(&vc).unwrap(), &signed);
+	assert_ne!(json, serde_json::to_string(&vp).unwrap());
+
+	assert_eq!(verify_presentation_raw(&ctx, json.as_bytes(), "nonce", "example.com"), Ok(()));
+	let errors = verify_presentation(&ctx, &vp, "nonce", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::InvalidCredential { index: 0, .. }]));
+
+	let errors =
+		verify_presentation_raw(&ctx, json.as_bytes(), "other", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::ChallengeMismatch { .. }]));
+	let errors = verify_presentation_raw(&ctx, b"{}", "nonce", "example.com").unwrap_err();
+	assert!(matches!(errors[..], [PresentationError::InvalidJson(_)]));
+}
+
+/// `value` with the members of every object in reverse order.
+fn reversed_json(value: &serde_json::Value) -> String {
+	match value {
+		serde_json::Value::Object(members) => {
+			let members: Vec<_> = members
+				.iter()
+				.rev()
+				.map(|(name, value)| format!("{}:{}", json!(name), reversed_json(value)))
+				.collect();
+			format!("{{{}}}", members.join(","))
+		},
+		serde_json::Value::Array(items) => {
+			let items: Vec<_> = items.iter().map(reversed_json).collect();
+			format!("[{}]", items.join(","))
+		},
+		_ => value.to_string(),
+	}
+}
+
+#[test]
+fn vc_jwt_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let mut vc = credential();
+	vc.expiration_timestamp = Some(1696663438999);
+
+	let jwt = encode_vc_jwt(&vc, &pair).unwrap();
+	let (header, claims) = decode_vc_jwt(&jwt).unwrap();
+	assert_eq!(header.alg, "EdDSA");
+	assert_eq!(claims.iss, hex::encode(ENCLAVE_ACCOUNT));
+	assert_eq!(claims.sub, SUBJECT);
+	assert_eq!(claims.nbf, 1696663438);
+	assert_eq!(claims.exp, Some(1696663438));
+	assert_eq!(claims.jti, vc.id);
+
+	vc.proof = None;
+	assert_eq!(verify_vc_jwt(&jwt, &pair.public()), Ok(vc));
+
+	let other = ed25519::Pair::from_seed(&[0x24; 32]);
+	assert_eq!(verify_vc_jwt(&jwt, &other.public()), Err(JwtError::InvalidSignature));
+}
+
+#[test]
+fn sd_jwt_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let mut vc = credential();
+	vc.proof = None;
+
+	let issued = SdJwt::issue(&vc, &pair).unwrap();
+	assert_eq!(issued.disclosures.len(), 2);
+	assert_eq!(SdJwt::parse(&issued.to_string()), Ok(issued.clone()));
+
+	let alice: KeyPair = sr25519::Pair::from_string("//Alice", None).unwrap().into();
+	let presented = issued.present(&[0], &alice, "nonce", "example.com", 0).unwrap();
+	let presented = SdJwt::parse(&presented.to_string()).unwrap();
+	let disclosed =
+		verify_sd_jwt(&presented, &pair.public(), Some(("nonce", "example.com"))).unwrap();
+	assert_eq!(disclosed.indexes, vec![0]);
+	assert_eq!(disclosed.credential, vc);
+
+	assert!(matches!(
+		verify_sd_jwt(&presented, &pair.public(), Some(("other nonce", "example.com"))),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+
+	let hidden = issued.present(&[], &alice, "nonce", "example.com", 0).unwrap();
+	let disclosed = verify_sd_jwt(&hidden, &pair.public(), None).unwrap();
+	assert!(disclosed.indexes.is_empty());
+	assert!(disclosed.credential.credential_subject.assertions.is_empty());
+
+	// Only the assertion is revealed, without its value.
+	let mut partial = presented.clone();
+	partial.disclosures.truncate(1);
+	assert!(matches!(
+		verify_sd_jwt(&partial, &pair.public(), None),
+		Err(JwtError::InvalidDisclosure(_))
+	));
+
+	// An ed25519 holder signs the key binding JWT with EdDSA.
+	let holder = ed25519::Pair::from_seed(&[0x11; 32]);
+	vc.credential_subject.id = hex::encode(holder.public().0);
+	let issued = SdJwt::issue(&vc, &pair).unwrap();
+	let presented = issued.present(&[0], &holder.into(), "nonce", "example.com", 0).unwrap();
+	let key_binding = presented.key_binding.as_deref().unwrap();
+	let header =
+		base64::decode_config(key_binding.split('.').next().unwrap(), base64::URL_SAFE_NO_PAD);
+	let header: serde_json::Value = serde_json::from_slice(&header.unwrap()).unwrap();
+	assert_eq!(header["alg"], "EdDSA");
+	let disclosed =
+		verify_sd_jwt(&presented, &pair.public(), Some(("nonce", "example.com"))).unwrap();
+	assert_eq!(disclosed.indexes, vec![0]);
+
+	let other: KeyPair = ed25519::Pair::from_seed(&[0x12; 32]).into();
+	let presented = issued.present(&[0], &other, "nonce", "example.com", 0).unwrap();
+	assert!(matches!(
+		verify_sd_jwt(&presented, &pair.public(), Some(("nonce", "example.com"))),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+	// A presented key binding is verified even when the verifier expects none.
+	assert!(matches!(
+		verify_sd_jwt(&presented, &pair.public(), None),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+
+	// The key binding can't be issued in the future, nor before the `max_age` window.
+	let holder: KeyPair = ed25519::Pair::from_seed(&[0x11; 32]).into();
+	let presented = issued.present(&[0], &holder, "nonce", "example.com", 1696663438).unwrap();
+	let key_binding = Some(("nonce", "example.com"));
+	let time = TimePolicy::at(1696663438000);
+	assert!(verify_sd_jwt_with(&presented, &pair.public(), key_binding, &time).is_ok());
+	assert!(matches!(
+		verify_sd_jwt_with(&presented, &pair.public(), key_binding, &TimePolicy::at(1696663000000)),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+	let time = TimePolicy::at(1696663438000 + 3_600_000).with_max_age(60_000);
+	assert!(matches!(
+		verify_sd_jwt_with(&presented, &pair.public(), key_binding, &time),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+	let future = issued.present(&[0], &holder, "nonce", "example.com", u64::MAX / 1000).unwrap();
+	assert!(matches!(
+		verify_sd_jwt(&future, &pair.public(), None),
+		Err(JwtError::InvalidKeyBinding(_))
+	));
+}
+
+#[test]
+fn data_integrity_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+
+	let document =
+		sign_vc_data_integrity(&credential(), &pair, Cryptosuite::EddsaJcs2022, 1696663438000)
+			.unwrap();
+	assert_eq!(document["proof"]["created"], "2023-10-07T07:23:58Z");
+	assert_eq!(verify_data_integrity(&document, &pair.public()), Ok(()));
+
+	let mut tampered = document.clone();
+	tampered["credentialSubject"]["values"] = json!([false]);
+	assert_eq!(
+		verify_data_integrity(&tampered, &pair.public()),
+		Err(DataIntegrityError::InvalidSignature)
+	);
+
+	// `issuanceTimestamp`, `credentialSubject.values`... aren't defined by the W3C contexts.
+	assert!(matches!(
+		sign_vc_data_integrity(&credential(), &pair, Cryptosuite::EddsaRdfc2022, 1696663438000),
+		Err(DataIntegrityError::UnsafeJsonLd(_))
+	));
+
+	let document = json!({
+		"@context": [
+			"https://www.w3.org/2018/credentials/v1",
+			"https://w3id.org/security/suites/ed25519-2020/v1"
+		],
+		"id": "urn:uuid:2c5c8c2e-7b0c-4a59-9a8b-5a2b1d7b0c01",
+		"type": ["VerifiableCredential"],
+		"issuer": "did:example:issuer",
+		"issuanceDate": "2023-10-07T07:23:58Z",
+		"credentialSubject": { "id": "did:example:subject" }
+	});
+	let document =
+		add_data_integrity_proof(&document, &pair, Cryptosuite::EddsaRdfc2022, 1696663438000)
+			.unwrap();
+	assert_eq!(verify_data_integrity(&document, &pair.public()), Ok(()));
+
+	// The RDF dataset is signed, not its JSON serialisation.
+	let mut equivalent = document.clone();
+	equivalent["issuer"] = json!({ "id": "did:example:issuer" });
+	assert_eq!(verify_data_integrity(&equivalent, &pair.public()), Ok(()));
+
+	let mut tampered = document;
+	tampered["issuanceDate"] = json!("2023-10-08T07:23:58Z");
+	assert_eq!(
+		verify_data_integrity(&tampered, &pair.public()),
+		Err(DataIntegrityError::InvalidSignature)
+	);
+}
+
+/// The test vectors of https://www.w3.org/TR/vc-di-eddsa/ (appendix A)
+#[test]
+fn data_integrity_test_vectors_work() {
+	// `z3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq` without its multicodec prefix
+	let seed = hex::decode("c96ef9ea10c5e414c471723aff9de72c35fa5b70fae97e8832ecac7d2e2b8ed6");
+	let pair = ed25519::Pair::from_seed_slice(&seed.unwrap()).unwrap();
+	let created = 1677281798000; // 2023-02-24T23:36:38Z
+	let verification_method = "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2#z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2";
+
+	let mut credential = json!({
+		"@context": [
+			"https://www.w3.org/ns/credentials/v2",
+			"https://www.w3.org/ns/credentials/examples/v2"
+		],
+		"id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+		"type": ["VerifiableCredential", "AlumniCredential"],
+		"name": "Alumni Credential",
+		"description": "A minimum viable example of an Alumni Credential.",
+		"issuer": "https://vc.example/issuers/5678",
+		"validFrom": "2023-01-01T00:00:00Z",
+		"credentialSubject": {
+			"id": "did:example:abcdefgh",
+			"alumniOf": "The School of Examples"
+		}
+	});
+
+	let document =
+		add_data_integrity_proof(&credential, &pair, Cryptosuite::EddsaJcs2022, created).unwrap();
+	assert_eq!(document["proof"]["verificationMethod"], verification_method);
+	assert_eq!(
+		document["proof"]["proofValue"],
+		"z2HnFSSPPBzR36zdDgK8PbEHeXbR56YF24jwMpt3R1eHXQzJDMWS93FCzpvJpwTWd3GAVFuUfjoJdcnTMuVor51aX"
+	);
+	assert_eq!(verify_data_integrity(&document, &pair.public()), Ok(()));
+
+	// The credentials v2 and examples contexts aren't bundled, the terms the credential uses are
+	// inlined instead. The RDF dataset, and so the proof value, are the ones of the spec.
+	credential["@context"] = json!([{
+		"@vocab": "https://www.w3.org/ns/credentials/examples#",
+		"id": "@id",
+		"type": "@type",
+		"VerifiableCredential": "https://www.w3.org/2018/credentials#VerifiableCredential",
+		"name": "https://schema.org/name",
+		"description": "https://schema.org/description",
+		"issuer": { "@id": "https://www.w3.org/2018/credentials#issuer", "@type": "@id" },
+		"validFrom": {
+			"@id": "https://www.w3.org/2018/credentials#validFrom",
+			"@type": "http://www.w3.org/2001/XMLSchema#dateTime"
+		},
+		"credentialSubject": {
+			"@id": "https://www.w3.org/2018/credentials#credentialSubject",
+			"@type": "@id"
+		}
+	}]);
+	let canonical = [
+		r#"<did:example:abcdefgh> <https://www.w3.org/ns/credentials/examples#alumniOf> "The School of Examples" ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/2018/credentials#VerifiableCredential> ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <https://www.w3.org/ns/credentials/examples#AlumniCredential> ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <https://schema.org/description> "A minimum viable example of an Alumni Credential." ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <https://schema.org/name> "Alumni Credential" ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <https://www.w3.org/2018/credentials#credentialSubject> <did:example:abcdefgh> ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <https://www.w3.org/2018/credentials#issuer> <https://vc.example/issuers/5678> ."#,
+		r#"<urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33> <https://www.w3.org/2018/credentials#validFrom> "2023-01-01T00:00:00Z"^^<http://www.w3.org/2001/XMLSchema#dateTime> ."#,
+	];
+	assert_eq!(
+		rdfc::canonicalize(&to_rdf(&credential).unwrap()),
+		Ok(canonical.iter().map(|quad| format!("{}\n", quad)).collect::<String>())
+	);
+
+	let document =
+		add_data_integrity_proof(&credential, &pair, Cryptosuite::EddsaRdfc2022, created).unwrap();
+	assert_eq!(
+		document["proof"]["proofValue"],
+		"z2YwC8z3ap7yx1nZYCg4L3j3ApHsF8kgPdSb5xoS1VR7vPG3F561B52hYnQF9iseabecm3ijx4K1FBTQsCZahKZme"
+	);
+	assert_eq!(verify_data_integrity(&document, &pair.public()), Ok(()));
+}
+
+#[test]
+fn credential_v2_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let vc = signed_credential(&pair);
+
+	let vc_v2 = CredentialV2::try_from(vc.clone()).unwrap();
+	assert_eq!(vc_v2.context[0], "https://www.w3.org/ns/credentials/v2");
+	assert_eq!(vc_v2.valid_from, "2023-10-07T07:23:58.000Z");
+	assert_eq!(Credential::try_from(vc_v2.clone()), Ok(vc.clone()));
+
+	let ctx = VerifyContext::new(pair.public());
+	let json = serde_json::to_string(&vc_v2).unwrap();
+	let versioned = VersionedCredential::from_json(&json).unwrap();
+	assert_eq!(versioned, VersionedCredential::V2(vc_v2.clone()));
+	assert!(verify_versioned_vc_with(&ctx, &versioned).unwrap().is_verified());
+
+	// Signed in the v2 layout
+	let mut native = vc_v2;
+	native.proof = None;
+	let signature = pair.sign(serde_json::to_string(&native).unwrap().as_bytes());
+	native.proof = vc.proof.map(|mut proof| {
+		proof.proof_value = hex::encode(signature.0);
+		proof
+	});
+	let report = verify_versioned_vc_with(&ctx, &VersionedCredential::V2(native.clone())).unwrap();
+	assert!(report.is_verified());
+
+	native.credential_subject.values = vec![false];
+	let report = verify_versioned_vc_with(&ctx, &VersionedCredential::V2(native)).unwrap();
+	assert!(!report.is_verified());
+}
+
+#[test]
+fn verification_report_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let mut vc = signed_credential(&pair);
+	let ctx =
+		VerifyContext::new(pair.public()).with_time(TimePolicy::at(vc.issuance_timestamp + 1000));
+
+	let report = verify_vc_with(&ctx, &vc);
+	assert_eq!(report.checks.len(), Check::ALL.len());
+	assert_eq!(report.outcome(Check::Proof), Some(&CheckOutcome::Pass));
+	assert_eq!(report.outcome(Check::Time), Some(&CheckOutcome::Pass));
+	assert!(matches!(report.outcome(Check::Issuer), Some(CheckOutcome::Skipped(_))));
+	assert_eq!(report.verified(), Ok(()));
+
+	let error = report.strict().unwrap_err();
+	let skipped: Vec<_> = error.checks.iter().map(|(check, _)| *check).collect();
+	assert_eq!(skipped, vec![Check::Issuer, Check::Status]);
+
+	vc.credential_subject.values = vec![false];
+	let report = verify_vc_with(&ctx, &vc);
+	assert_eq!(report.outcome(Check::Proof), Some(&CheckOutcome::Fail("invalid signature".into())));
+	assert!(!report.is_verified());
+}
+
+#[test]
+fn verify_batch_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let vc = signed_credential(&pair);
+	let mut tampered = vc.clone();
+	tampered.credential_subject.values = vec![false];
+	let vcs = vec![vc.clone(), tampered, vc.clone(), vc];
+
+	let ctx = VerifyContext::new(pair.public());
+	let verifier = Verifier::new(pair.public()).unwrap();
+	let reports = verifier.verify_batch_with_threads(&vcs, 3);
+	assert_eq!(reports.len(), vcs.len());
+	for (vc, report) in vcs.iter().zip(&reports) {
+		assert_eq!(report, &verify_vc_with(&ctx, vc));
+	}
+	assert_eq!(reports.iter().filter(|report| report.is_verified()).count(), 3);
+	assert_eq!(verifier.verify_batch(&vcs), reports);
+}
+
+/// Counts the lookups which reach the registries.
+struct CountingLookup {
+	enclaves: EnclaveRegistrySnapshot,
+	registry: VCRegistry,
+	enclave_fetches: AtomicUsize,
+	registry_fetches: Mutex<HashMap<VCIndex, usize>>,
+}
+
+impl EnclaveLookup for CountingLookup {
+	fn enclaves(&self) -> CResult<Vec<Enclave<AccountId, String>>> {
+		self.enclave_fetches.fetch_add(1, Ordering::SeqCst);
+		self.enclaves.enclaves()
+	}
+}
+
+impl VCRegistryLookup for CountingLookup {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		*self.registry_fetches.lock().unwrap().entry(*index).or_default() += 1;
+		self.registry.lookup_vc_context(index)
+	}
+}
+
+#[test]
+fn verifier_caches_lookups() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let subject = AccountId::new(hex::decode(SUBJECT).unwrap().try_into().unwrap());
+	let vc = signed_credential(&pair);
+	let mut other = vc.clone();
+	other.id = hex_encode(&[0x11; 32]);
+
+	let mut lookup = CountingLookup {
+		enclaves: EnclaveRegistrySnapshot::new(vec![], &[enclave(MRENCLAVE)]),
+		registry: VCRegistry::default(),
+		enclave_fetches: AtomicUsize::new(0),
+		registry_fetches: Mutex::default(),
+	};
+	for vc in [&vc, &other] {
+		let index = VCIndex::from_slice(&hex::decode(&vc.id[2..]).unwrap());
+		let context = VCContext::new(subject.clone(), Assertion::A1, credential_hash(vc).unwrap());
+		lookup.registry.contexts.insert(index, context);
+	}
+
+	let vcs = vec![vc.clone(), other.clone(), vc.clone(), other.clone(), vc, other];
+	let verifier = Verifier::new(pair.public())
+		.unwrap()
+		.with_enclaves(&lookup)
+		.with_registry(&lookup)
+		.with_holder(subject);
+	let reports = verifier.verify_batch_with_threads(&vcs, 3);
+	assert_eq!(
+		reports
+			.iter()
+			.filter(|report| report.outcome(Check::Status) == Some(&CheckOutcome::Pass))
+			.count(),
+		6
+	);
+
+	assert_eq!(lookup.enclave_fetches.load(Ordering::SeqCst), 1);
+	let registry_fetches = lookup.registry_fetches.lock().unwrap();
+	assert_eq!(registry_fetches.len(), 2);
+	assert!(registry_fetches.values().all(|fetches| *fetches == 1));
+}
+
+#[test]
+fn verifier_checks_holder() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let vc = signed_credential(&pair);
+	let subject = AccountId::new(hex::decode(SUBJECT).unwrap().try_into().unwrap());
+
+	let verifier = Verifier::new(pair.public()).unwrap().with_holder(subject);
+	assert_eq!(verifier.verify(&vc).outcome(Check::Subject), Some(&CheckOutcome::Pass));
+
+	let verifier = Verifier::new(pair.public()).unwrap().with_holder(AccountId::new([0x11; 32]));
+	let report = verifier.verify(&vc);
+	assert!(matches!(report.outcome(Check::Subject), Some(CheckOutcome::Fail(_))));
+	assert!(!report.is_verified());
+}
+
+#[test]
+fn verifier_verifies_raw_credentials() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let signed = signed_json_with_unknown_member(&pair);
+	let verifier = Verifier::new(pair.public()).unwrap();
+
+	// The unknown member is serialised back at another position, so only the raw JSON verifies.
+	let vc: Credential = serde_json::from_str(&signed).unwrap();
+	assert!(!verifier.verify(&vc).is_verified());
+	assert!(verifier.verify_raw(signed.as_bytes()).unwrap().is_verified());
+
+	let tampered = signed.replace("\"values\":[true]", "\"values\":[false]");
+	let jsons = [signed.as_bytes(), b"{}", tampered.as_bytes()];
+	let reports = verifier.verify_raw_batch_with_threads(&jsons, 2);
+	assert!(reports[0].as_ref().unwrap().is_verified());
+	assert!(reports[1].is_err());
+	assert!(!reports[2].as_ref().unwrap().is_verified());
+	assert_eq!(verifier.verify_raw_batch(&jsons), reports);
+}
+
+/// Panics on the lookup of `index`.
+struct PanickingRegistry {
+	index: VCIndex,
+}
+
+impl VCRegistryLookup for PanickingRegistry {
+	fn lookup_vc_context(&self, index: &VCIndex) -> CResult<Option<Vec<u8>>> {
+		assert_ne!(index, &self.index, "lookup failed");
+		Ok(None)
+	}
+}
+
+#[test]
+fn verifier_survives_panics() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let vc = signed_credential(&pair);
+	let mut other = vc.clone();
+	other.id = hex_encode(&[0x11; 32]);
+
+	let registry = PanickingRegistry { index: VCIndex::from_slice(&[0x11; 32]) };
+	let verifier = Verifier::new(pair.public()).unwrap().with_registry(&registry);
+	let reports = verifier.verify_batch_with_threads(&[vc, other.clone(), other], 2);
+	assert_eq!(reports.len(), 3);
+	assert_eq!(reports[0].outcome(Check::Proof), Some(&CheckOutcome::Pass));
+	assert_eq!(reports[1], VerificationReport::failed("verifier thread panicked"));
+	assert_eq!(reports[2], reports[1]);
+}
+
+#[test]
+fn credential_builder_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+	let subject = Identity::from(alice.public());
+
+	let vc = CredentialBuilder::new(&Assertion::A1, &subject)
+		.with_issuer(&ENCLAVE_ACCOUNT, &MRENCLAVE)
+		.issued_at(1696663438000)
+		.sign(&pair)
+		.unwrap();
+	assert_eq!(vc.credential_subject.id, SUBJECT);
+	assert_eq!(vc.credential_subject.types, "Basic Identity Verification");
+	assert_eq!(vc.credential_subject.assertions, credential().credential_subject.assertions);
+	assert_eq!(vc.credential_subject.values, vec![true]);
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+
+	let ctx = VerifyContext::new(pair.public()).with_holder(subject.clone());
+	assert_eq!(verify_vc_with(&ctx, &vc).verified(), Ok(()));
+
+	let vc =
+		CredentialBuilder::new(&Assertion::A4("10".to_string()), &Identity::from(alice.public()))
+			.with_values(vec![false])
+			.sign(&pair)
+			.unwrap();
+	assert_eq!(vc.credential_subject.values, vec![false]);
+	assert!(verify_vc_with(&VerifyContext::new(pair.public()), &vc).is_verified());
+
+	// The fetched items are added to the `and` of the assertion.
+	let a4 = || {
+		CredentialBuilder::new(&Assertion::A4("1.001".to_string()), &Identity::from(alice.public()))
+			.with_assertion(item("$from_date", Op::LessThan, "2017-01-01"))
+			.with_assertion(item("$to_date", Op::GreaterEq, "2023-04-13"))
+	};
+	let vc = a4().sign(&pair).unwrap();
+	let example: serde_json::Value =
+		serde_json::from_str(include_str!("../docs/vc-examples/a4.json")).unwrap();
+	let assertions: Vec<AssertionLogic> =
+		serde_json::from_value(example["credentialSubject"]["assertions"].clone()).unwrap();
+	assert_eq!(vc.credential_subject.assertions, assertions);
+	assert_eq!(vc.credential_subject.values, vec![true]);
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+	assert!(a4().with_values(vec![true, false]).sign(&pair).is_err());
+
+	// Only A1 to A11 have a known subject info and logic.
+	let crypto_summary = || CredentialBuilder::new(&Assertion::CryptoSummary, &subject);
+	let subject_info = ("Crypto Summary", "Generate a summary of your on-chain identity");
+	assert!(crypto_summary().sign(&pair).is_err());
+	assert!(crypto_summary()
+		.with_subject_info(subject_info.0, subject_info.1, &["Ethereum", "BSC"])
+		.sign(&pair)
+		.is_err());
+	let vc = crypto_summary()
+		.with_subject_info(subject_info.0, subject_info.1, &["Ethereum", "BSC"])
+		.with_assertion(item("$holding_lit", Op::Equal, "true"))
+		.sign(&pair)
+		.unwrap();
+	assert_eq!(vc.credential_subject.assertions.len(), 1);
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+}
+
+/// Builds the credential of `assertion` for Alice with the `values` and `endpoint` of `example`,
+/// its subject must be the one of `example`. `with_dates` adds the dates of the example.
+fn assert_builds_example(assertion: Assertion, with_dates: bool, example: &str) {
+	let example: serde_json::Value = serde_json::from_str(example).unwrap();
+	let subject = &example["credentialSubject"];
+	let values: Vec<bool> = serde_json::from_value(subject["values"].clone()).unwrap();
+
+	let alice = sr25519::Pair::from_string("//Alice", None).unwrap();
+	let mut builder = CredentialBuilder::new(&assertion, &Identity::from(alice.public()))
+		.with_values(values)
+		.with_endpoint(subject["endpoint"].as_str().unwrap());
+	if with_dates {
+		builder = builder
+			.with_assertion(item("$from_date", Op::LessThan, "2017-01-01"))
+			.with_assertion(item("$to_date", Op::GreaterEq, "2023-04-13"));
+	}
+	let vc = builder.sign(&ed25519::Pair::from_seed(&[0x42; 32])).unwrap();
+
+	assert_eq!(serde_json::to_value(&vc.credential_subject).unwrap(), *subject);
+	assert_eq!(validate_vc_schema(&vc), Ok(()));
+}
+
+#[test]
+fn credential_builder_builds_a1_example() {
+	assert_builds_example(Assertion::A1, false, include_str!("../docs/vc-examples/a1.json"));
+}
+
+#[test]
+fn credential_builder_builds_a2_example() {
+	let assertion = Assertion::A2("guild_id".to_string());
+	assert_builds_example(assertion, false, include_str!("../docs/vc-examples/a2.json"));
+}
+
+#[test]
+fn credential_builder_builds_a3_example() {
+	let assertion =
+		Assertion::A3("guild_id".to_string(), "channel_id".to_string(), "role_id".to_string());
+	assert_builds_example(assertion, false, include_str!("../docs/vc-examples/a3.json"));
+}
+
+#[test]
+fn credential_builder_builds_a4_example() {
+	let assertion = Assertion::A4("1.001".to_string());
+	assert_builds_example(assertion, true, include_str!("../docs/vc-examples/a4.json"));
+}
+
+// There's no A5 `Assertion` for `docs/vc-examples/a5.json` anymore.
+
+#[test]
+fn credential_builder_builds_a6_example() {
+	assert_builds_example(Assertion::A6, false, include_str!("../docs/vc-examples/a6.json"));
+}
+
+#[test]
+fn credential_builder_builds_a7_example() {
+	let assertion = Assertion::A7("1.001".to_string());
+	assert_builds_example(assertion, true, include_str!("../docs/vc-examples/a7.json"));
+}
+
+#[test]
+fn credential_builder_builds_a8_example() {
+	let networks = vec![Web3Network::Litentry].try_into().unwrap();
+	assert_builds_example(
+		Assertion::A8(networks),
+		false,
+		include_str!("../docs/vc-examples/a8.json"),
+	);
+}
+
+#[test]
+fn credential_builder_builds_a10_example() {
+	let assertion = Assertion::A10("1.001".to_string());
+	assert_builds_example(assertion, true, include_str!("../docs/vc-examples/a10.json"));
+}
+
+#[test]
+fn credential_builder_builds_a11_example() {
+	let assertion = Assertion::A11("1.001".to_string());
+	assert_builds_example(assertion, true, include_str!("../docs/vc-examples/a11.json"));
+}
+
+fn item(src: &str, op: Op, dst: &str) -> AssertionLogic {
+	AssertionLogic::Item { src: src.to_string(), op, dst: dst.to_string() }
+}
+
+/// A credential issued by a newer worker, with a member `Credential` doesn't know about.
+fn signed_json_with_unknown_member(pair: &ed25519::Pair) -> String {
+	let mut vc = credential();
+	vc.proof = None;
+
+	let mut value = serde_json::to_value(&vc).unwrap();
+	value["credentialSubject"]["dataSource"] =
+		json!([{ "dataProviderId": 1, "dataProvider": "x" }]);
+	value["futureField"] = json!({ "a": [1, "}"] });
+	let unsigned = serde_json::to_string(&value).unwrap();
+	let signature = pair.sign(unsigned.as_bytes());
+
+	let mut proof = serde_json::to_value(credential().proof).unwrap();
+	proof["proofValue"] = json!(hex::encode(signature.0));
+	format!("{},\"proof\":{}}}", &unsigned[..unsigned.len() - 1], proof)
+}
+
+#[test]
+fn verify_vc_proof_raw_works() {
+	let pair = ed25519::Pair::from_seed(&[0x42; 32]);
+	let signed = signed_json_with_unknown_member(&pair);
+
+	assert_eq!(verify_vc_proof_raw(&pair.public(), signed.as_bytes()), Ok(true));
+	let vc: Credential = serde_json::from_str(&signed).unwrap();
+	assert_eq!(verify_vc_proof(&pair.public(), &vc), Ok(false));
+
+	let tampered = signed.replace("\"values\":[true]", "\"values\":[false]");
+	assert_eq!(verify_vc_proof_raw(&pair.public(), tampered.as_bytes()), Ok(false));
+}
+
+#[test]
+fn credential_round_trip_works() {
+	// Known members in declaration order, then the unknown ones in key order.
+	let json = concat!(
+		r#"{"@context":["https://www.w3.org/2018/credentials/v1"],"id":"0x01","#,
+		r#""type":["VerifiableCredential"],"#,
+		r#""credentialSubject":{"id":"d435","description":"d","type":"t","tag":[],"#,
+		r#""dataSource":[{"dataProviderId":1,"dataProvider":"https://example.com"}],"#,
+		r#""assertions":[],"values":[],"endpoint":"wss://rpc.litentry-parachain.litentry.io","rank":3},"#,
+		r#""issuer":{"id":"43","name":"n","mrenclave":"m","shard":"s"},"issuanceTimestamp":1,"#,
+		r#""proof":{"createdTimestamp":1,"type":"Ed25519Signature2020","#,
+		r#""proofPurpose":"assertionMethod","proofValue":"00","verificationMethod":"43"},"#,
+		r#""credentialSchema":{"id":"s1","type":"JsonSchemaValidator2018"},"#,
+		r#""evidence":[{"a":1,"b":2}],"termsOfUse":null}"#
+	);
+
+	let vc: Credential = serde_json::from_str(json).unwrap();
+	assert_eq!(vc.credential_subject.data_source.as_ref().unwrap()[0].data_provider_id, 1);
+	assert_eq!(vc.credential_schema.as_ref().unwrap().id, "s1");
+	assert_eq!(vc.credential_subject.extra["rank"], json!(3));
+	assert_eq!(vc.issuer.extra["shard"], json!("s"));
+	assert_eq!(vc.extra.keys().collect::<Vec<_>>(), vec!["evidence", "termsOfUse"]);
+	assert_eq!(serde_json::to_string(&vc).unwrap(), json);
+
+	let vc_v2 = CredentialV2::try_from(vc.clone()).unwrap();
+	assert_eq!(vc_v2.extra, vc.extra);
+	assert_eq!(Credential::try_from(vc_v2), Ok(vc));
+}
+
+#[test]
+fn verify_vc_registered_schema_works() {
+	let mut json = serde_json::to_value(credential()).unwrap();
+	json["credentialSchema"] = json!({ "id": "s1", "type": "JsonSchemaValidator2018" });
+	let vc: Credential = serde_json::from_value(json).unwrap();
+
+	// As read from `VCManagement::SchemaRegistry`
+	let schema = |required: &str, status: Status| {
+		let content = json!({ "type": "object", "required": ["credentialSchema", required] });
+		let encoded = (
+			b"s1".to_vec(),
+			AccountId::new(ENCLAVE_ACCOUNT),
+			content.to_string().into_bytes(),
+			status,
+		)
+			.encode();
+		VCSchema::decode(&mut encoded.as_slice()).unwrap()
+	};
+
+	assert_eq!(verify_vc_registered_schema(&vc, &schema("issuer", Status::Active)), Ok(true));
+	assert_eq!(
+		verify_vc_registered_schema(&vc, &schema("expirationTimestamp", Status::Active)),
+		Ok(false)
+	);
+	assert_eq!(verify_vc_registered_schema(&vc, &schema("issuer", Status::Disabled)), Ok(false));
+	assert!(verify_vc_registered_schema(&credential(), &schema("issuer", Status::Active)).is_err());
+}